@@ -1,11 +1,20 @@
 use std::sync::{Arc, RwLock};
 
+use chrono::Utc;
+use serenity::async_trait;
+use serenity::utils::parse_username;
+
 use crate::config::{Config, Role};
 use crate::commands::{Command, CommandError};
-use crate::util::perms::check_permission;
+use crate::storage::{Grant, Storage};
+use crate::util::duration::{format_duration, parse_duration};
+use crate::util::logs::reload_rules;
+use crate::util::perms::{check_permission, unscoped};
+use crate::util::recipes::reload_recipes;
 
 pub struct ReloadCommand;
 
+#[async_trait]
 impl Command for ReloadCommand {
     fn name(&self) -> &'static str {
         "reload"
@@ -16,13 +25,130 @@ impl Command for ReloadCommand {
     fn about(&self) -> &'static str {
         "Reloads the bot's config file"
     }
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, _input: String) -> Result<String, CommandError> {
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, _input: String) -> Result<String, CommandError> {
         if check_permission(&config.read().unwrap(), "admin.reload".to_string(), role) {
             let mut c = config.write().unwrap();
             *c = Config::get();
+            reload_rules(&c.logs.rules);
+            reload_recipes(&c.recipes);
             return Ok("Config reloaded!".to_string());
         } else {
             return Err(CommandError::NoPerms);
         }
     }
-}
\ No newline at end of file
+}
+
+// Get a user ID from either a mention or a plain ID
+fn parse_user(input: &str) -> Option<u64> {
+    parse_username(input).or_else(|| input.parse::<u64>().ok())
+}
+
+pub struct GrantCommand {
+    pub storage: Arc<RwLock<Storage>>,
+}
+
+#[async_trait]
+impl Command for GrantCommand {
+    fn name(&self) -> &'static str {
+        "grant"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <@user> <(-)permission> (duration)"
+    }
+    fn about(&self) -> &'static str {
+        "Grants or denies a permission for a single user, optionally expiring after a duration like 2h or 1d12h"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "admin.grant".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+
+        let mut args = input.split_whitespace();
+        let user = args.next().and_then(parse_user);
+        let perm = args.next().unwrap_or_default().to_lowercase();
+        let duration = args.next();
+        let (user, perm) = match user {
+            Some(user) if !perm.is_empty() && perm != "-" => (user, perm),
+            _ => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+        };
+
+        // Only permissions the granter has can be handed out, so granting can't be used to get more permissions.
+        // The role only holds the permissions for the channel the command is used in, without their qualifiers.
+        let node = perm.trim_start_matches('-').to_string();
+        if !check_permission(&config.read().unwrap(), unscoped(&node).to_string(), role) {
+            return Err(CommandError::BadUsage(format!("You can only grant or deny permissions you have yourself, which `{}` isn't", node)));
+        }
+
+        let now = Utc::now().timestamp();
+        let expires = match duration {
+            Some(d) => match parse_duration(d) {
+                Some(seconds) => match now.checked_add(seconds) {
+                    Some(expires) => Some(expires),
+                    None => return Err(CommandError::BadUsage(format!("{} is too long", d))),
+                },
+                None => return Err(CommandError::BadUsage(format!("{} is not a valid duration, try something like 30m, 2h or 1d", d))),
+            },
+            None => None,
+        };
+
+        let mut storage = self.storage.write().unwrap();
+        // Replace any earlier grant or denial of the same permission, and clean up expired ones
+        storage.grants.retain(|g| g.is_active(now) && !(g.user == user && g.perm.trim_start_matches('-') == node));
+        storage.grants.push(Grant { user, perm: perm.to_string(), expires });
+        storage.save();
+
+        let action = if perm.starts_with('-') { "Denied" } else { "Granted" };
+        match expires {
+            Some(e) => Ok(format!("{} `{}` for <@{}> for {}", action, node, user, format_duration(e - now))),
+            None => Ok(format!("{} `{}` for <@{}> until revoked", action, node, user)),
+        }
+    }
+}
+
+pub struct RevokeCommand {
+    pub storage: Arc<RwLock<Storage>>,
+}
+
+#[async_trait]
+impl Command for RevokeCommand {
+    fn name(&self) -> &'static str {
+        "revoke"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <@user> (permission)"
+    }
+    fn about(&self) -> &'static str {
+        "Removes a permission granted or denied with the grant command, or all of them if no permission is given"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "admin.grant".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+
+        let mut args = input.split_whitespace();
+        let user = match args.next().and_then(parse_user) {
+            Some(user) => user,
+            None => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+        };
+        let node = args.next().unwrap_or_default().trim_start_matches('-').to_lowercase();
+
+        let now = Utc::now().timestamp();
+        let mut storage = self.storage.write().unwrap();
+        storage.grants.retain(|g| g.is_active(now));
+        let matches = |g: &Grant| g.user == user && (node.is_empty() || g.perm.trim_start_matches('-') == node);
+        // The same as for granting, as revoking a denial gives the permission back
+        let config = config.read().unwrap();
+        if let Some(g) = storage.grants.iter().find(|g| matches(g) && !check_permission(&config, unscoped(g.perm.trim_start_matches('-')).to_string(), role)) {
+            return Err(CommandError::BadUsage(format!("You can only revoke permissions you have yourself, which `{}` isn't", g.perm.trim_start_matches('-'))));
+        }
+        let before = storage.grants.len();
+        storage.grants.retain(|g| !matches(g));
+        let removed = before - storage.grants.len();
+        storage.save();
+
+        if removed == 0 {
+            return Err(CommandError::BadUsage(format!("<@{}> has no matching grants", user)));
+        }
+        Ok(format!("Removed {} grant{} from <@{}>", removed, if removed == 1 { "" } else { "s" }, user))
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use serenity::async_trait;
+use serenity::client::Context;
+use serenity::model::channel::Message;
+use serenity::model::id::UserId;
+use serenity::utils::parse_username;
+
+use crate::config::{Config, Role};
+use crate::commands::{Command, CommandError};
+use crate::storage::{Application, ApplicationStatus, Storage};
+use crate::util::applications::next_question;
+use crate::util::bridge::bridged_player;
+use crate::util::duration::format_duration;
+use crate::util::perms::check_permission;
+use crate::util::uuid::valid_name;
+
+// Only list this many applications at once
+const MAX_LISTED: usize = 15;
+
+pub struct ApplyCommand {
+    pub storage: Arc<RwLock<Storage>>,
+}
+
+#[async_trait]
+impl Command for ApplyCommand {
+    fn name(&self) -> &'static str {
+        "apply"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <Minecraft name>"
+    }
+    fn about(&self) -> &'static str {
+        "Applies to be whitelisted on the Minecraft server, by answering a few questions for the staff"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, _input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.apply".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        Err(CommandError::BadUsage("Applications can only be made from Discord".to_string()))
+    }
+    async fn execute_message(&self, ctx: &Context, msg: &Message, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.apply".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let player = input.trim();
+        let (settings, bridged) = {
+            let config = config.read().unwrap();
+            (config.applications.clone(), bridged_player(&config, msg).is_some())
+        };
+        if settings.channel == 0 {
+            return Err(CommandError::BadUsage("Applications aren't set up on this server".to_string()));
+        }
+        let Some(guild) = msg.guild_id.filter(|_| !bridged) else {
+            return Err(CommandError::BadUsage("Applications can only be made from Discord, in the server".to_string()));
+        };
+        if !valid_name(player) {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+        let user = *msg.author.id.as_u64();
+        if let Some(a) = self.storage.read().unwrap().applications.iter().find(|a| a.user == user && a.status == ApplicationStatus::Pending) {
+            return Err(CommandError::BadUsage(format!("Your application for {} is still waiting to be reviewed", a.player)));
+        }
+
+        // Ask the questions in DMs, or in a private thread with the applicant
+        let (channel, reply) = if settings.form.eq_ignore_ascii_case("thread") {
+            let thread = msg.channel_id.create_private_thread(ctx, |t| t.name(format!("Application {}", player))).await
+                .map_err(|e| CommandError::BadUsage(format!("I couldn't make a thread for your application: {}", e)))?;
+            thread.id.add_thread_member(ctx, msg.author.id).await
+                .map_err(|e| CommandError::BadUsage(format!("I couldn't add you to the thread for your application: {}", e)))?;
+            (thread.id, format!("Answer the questions in <#{}> to apply", thread.id))
+        } else {
+            let dm = msg.author.create_dm_channel(ctx).await
+                .map_err(|_| CommandError::BadUsage("I couldn't DM you, allow DMs from server members and try again".to_string()))?;
+            (dm.id, "I sent you the questions in DMs".to_string())
+        };
+
+        let id = {
+            let mut storage = self.storage.write().unwrap();
+            // Applying again starts over
+            storage.applications.retain(|a| !(a.user == user && a.status == ApplicationStatus::Open));
+            let id = storage.applications.iter().map(|a| a.id).max().unwrap_or(0) + 1;
+            storage.applications.push(Application {
+                id,
+                user,
+                player: player.to_string(),
+                guild: *guild.as_u64(),
+                status: ApplicationStatus::Open,
+                channel: *channel.as_u64(),
+                message: 0,
+                created: Utc::now().timestamp(),
+                reviewer: 0,
+                reviewed: 0,
+                answers: Vec::new(),
+            });
+            storage.save();
+            id
+        };
+        if let Err(e) = next_question(ctx, &config, &self.storage, id).await {
+            return Err(CommandError::BadUsage(format!("I couldn't send you the questions: {}", e)));
+        }
+        Ok(reply)
+    }
+}
+
+pub struct ApplicationsCommand {
+    pub storage: Arc<RwLock<Storage>>,
+}
+
+impl ApplicationsCommand {
+    // Show applications, using the context to show Discord names if there is one
+    async fn list(&self, ctx: Option<&Context>, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.applications".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let input = input.trim();
+        let applications = self.storage.read().unwrap().applications.clone();
+
+        // A single application with its answers
+        if let Ok(id) = input.trim_start_matches('#').parse::<u64>() {
+            if let Some(a) = applications.iter().find(|a| a.id == id) {
+                let mut lines = vec![describe(ctx, a).await];
+                lines.extend(a.answers.iter().map(|a| format!("**{}** {}", a.question, a.answer)));
+                return Ok(lines.join("\n").chars().take(1900).collect());
+            }
+        }
+
+        let statuses = [ApplicationStatus::Open, ApplicationStatus::Pending, ApplicationStatus::Approved, ApplicationStatus::Denied];
+        let (what, found): (String, Vec<&Application>) = match (input.to_lowercase().as_str(), parse_username(input)) {
+            ("", _) => ("pending applications".to_string(), applications.iter().filter(|a| a.status == ApplicationStatus::Pending).collect()),
+            ("all", _) => ("applications".to_string(), applications.iter().collect()),
+            (_, Some(user)) => ("applications from that user".to_string(), applications.iter().filter(|a| a.user == user).collect()),
+            (status, None) => match statuses.iter().find(|s| s.name() == status) {
+                Some(s) => (format!("{} applications", s.name()), applications.iter().filter(|a| a.status == *s).collect()),
+                None if valid_name(input) => (format!("applications for {}", input), applications.iter().filter(|a| a.player.eq_ignore_ascii_case(input)).collect()),
+                None => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+            },
+        };
+        if found.is_empty() {
+            return Ok(format!("There are no {}", what));
+        }
+
+        let mut lines = vec![format!("{} {}, newest first:", found.len(), what)];
+        for a in found.iter().rev().take(MAX_LISTED) {
+            lines.push(describe(ctx, a).await);
+        }
+        Ok(lines.join("\n").chars().take(1900).collect())
+    }
+}
+
+// One line about an application, like "#3 Steve from Alex, approved by Bob 2h ago"
+async fn describe(ctx: Option<&Context>, application: &Application) -> String {
+    let now = Utc::now().timestamp();
+    let applicant = name(ctx, application.user).await;
+    match application.status {
+        ApplicationStatus::Open => format!("#{} {} from {}, answering the questions", application.id, application.player, applicant),
+        ApplicationStatus::Pending => format!("#{} {} from {}, pending for {}", application.id, application.player, applicant, format_duration(now - application.created)),
+        status => format!("#{} {} from {}, {} by {} {} ago", application.id, application.player, applicant, status.name(),
+            name(ctx, application.reviewer).await, format_duration(now - application.reviewed)),
+    }
+}
+
+// The name of a Discord user if there is a context to look it up with, otherwise their ID
+async fn name(ctx: Option<&Context>, user: u64) -> String {
+    match ctx {
+        Some(ctx) => match UserId(user).to_user(ctx).await {
+            Ok(u) => u.name,
+            Err(_) => user.to_string(),
+        },
+        None => user.to_string(),
+    }
+}
+
+#[async_trait]
+impl Command for ApplicationsCommand {
+    fn name(&self) -> &'static str {
+        "applications"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: (pending|approved|denied|open|all|#id|Minecraft name|@user)"
+    }
+    fn about(&self) -> &'static str {
+        "Shows whitelist applications, the pending ones by default"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        self.list(None, config, role, input).await
+    }
+    async fn execute_message(&self, ctx: &Context, _msg: &Message, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        self.list(Some(ctx), config, role, input).await
+    }
+}
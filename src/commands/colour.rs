@@ -0,0 +1,103 @@
+use std::borrow::Cow;
+use std::sync::{Arc, RwLock};
+
+use serenity::async_trait;
+use serenity::client::Context;
+use serenity::model::channel::{AttachmentType, Message};
+
+use crate::config::{Config, Role};
+use crate::commands::{Command, CommandError};
+use crate::util::colours::{nearest, shade, swatches, CONCRETE, DYES, DYE_NAMES, MAP_COLOURS, MAP_SHADES, TERRACOTTA, WOOL};
+use crate::util::format::parse_colour;
+use crate::util::items::closest;
+use crate::util::perms::check_permission;
+
+// The colour of plain terracotta, which has no dye colour
+const PLAIN_TERRACOTTA: u32 = 0x985E43;
+
+// Read a colour as a hex code, with or without the #, or as the name of a dye, block or text colour
+fn read_colour(input: &str) -> Option<u32> {
+    let hex = input.trim().trim_start_matches('#');
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return u32::from_str_radix(hex, 16).ok();
+    }
+    let mut names: Vec<(String, u32)> = vec![("Terracotta".to_string(), PLAIN_TERRACOTTA)];
+    for (i, name) in DYE_NAMES.iter().enumerate() {
+        names.push((name.to_string(), DYES[i]));
+        names.push((format!("{} Dye", name), DYES[i]));
+        names.push((format!("{} Wool", name), WOOL[i]));
+        names.push((format!("{} Concrete", name), CONCRETE[i]));
+        names.push((format!("{} Terracotta", name), TERRACOTTA[i]));
+    }
+    closest(input, names.iter().map(|(name, rgb)| (name.as_str(), *rgb)))
+        // Text colours use names like dark_aqua
+        .or_else(|| parse_colour(&input.trim().to_lowercase().replace(' ', "_")))
+}
+
+// What is closest to a colour, as lines of text and the colours for the swatches
+fn describe(rgb: u32) -> (String, Vec<u32>) {
+    // Every base map colour in the shades that can be placed in survival
+    let map: Vec<(&str, &str, u32)> = MAP_COLOURS.iter()
+        .flat_map(|(name, base)| MAP_SHADES.iter().map(move |(shade_name, m)| (*name, *shade_name, shade(*base, *m))))
+        .collect();
+    let (i, map_distance) = nearest(rgb, map.iter().map(|(_name, _shade, c)| *c)).unwrap_or_default();
+    let (map_name, map_shade, map_rgb) = map[i];
+
+    let mut lines = vec![
+        format!("Closest to #{:06X}:", rgb),
+        format!("Map colour: {}, {} #{:06X} (difference {:.1})", map_name, map_shade, map_rgb, map_distance),
+    ];
+    let mut colours = vec![rgb, map_rgb];
+    for (kind, table) in [("Dye", DYES), ("Wool", WOOL), ("Concrete", CONCRETE), ("Terracotta", TERRACOTTA)] {
+        let (i, d) = nearest(rgb, table.into_iter()).unwrap_or_default();
+        lines.push(format!("{}: {} {} #{:06X} (difference {:.1})", kind, DYE_NAMES[i], kind, table[i], d));
+        colours.push(table[i]);
+    }
+    lines.push("The swatches show the colour, then each of these in order.".to_string());
+    (lines.join("\n"), colours)
+}
+
+pub struct McColorCommand;
+
+#[async_trait]
+impl Command for McColorCommand {
+    fn name(&self) -> &'static str {
+        "mccolor"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <hex colour|name>"
+    }
+    fn about(&self) -> &'static str {
+        "Finds the map colour, dye, wool, concrete and terracotta that look closest to a colour."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mccolor".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        if input.trim().is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+        match read_colour(&input) {
+            Some(rgb) => Ok(describe(rgb).0),
+            None => Err(CommandError::BadUsage(format!("I don't know the colour {}, try a hex colour like #FF5555", input.trim()))),
+        }
+    }
+    async fn execute_message(&self, ctx: &Context, msg: &Message, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        // Check the permission and input the same way as without a message
+        self.execute(config, role, input.to_string()).await?;
+        let (text, colours) = describe(read_colour(&input).unwrap_or_default());
+        let png = swatches(&colours);
+        let sent = msg.channel_id.send_message(ctx, |m| m
+            .content(&text)
+            .reference_message(msg)
+            .add_file(AttachmentType::Bytes { data: Cow::from(png), filename: "colours.png".to_string() })).await;
+        match sent {
+            Ok(_) => Ok(String::new()),
+            // Still give the text if the image can't be sent, like without the Attach Files permission
+            Err(e) => {
+                println!("Error sending colour swatches: {:?}", e);
+                Ok(text)
+            },
+        }
+    }
+}
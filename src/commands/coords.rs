@@ -0,0 +1,170 @@
+use std::sync::{Arc, RwLock};
+
+use serenity::async_trait;
+
+use crate::config::{Config, MapConfig, Role};
+use crate::commands::{Command, CommandError};
+use crate::util::expression::parse_amount;
+use crate::util::perms::check_permission;
+
+// How far from the centre the world border can be
+const WORLD_LIMIT: f64 = 30_000_000.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dimension {
+    Overworld,
+    Nether,
+    End,
+}
+
+impl Dimension {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "overworld" | "ow" | "o" | "world" => Some(Dimension::Overworld),
+            "nether" | "n" | "hell" => Some(Dimension::Nether),
+            "end" | "e" | "the_end" => Some(Dimension::End),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Dimension::Overworld => "Overworld",
+            Dimension::Nether => "Nether",
+            Dimension::End => "End",
+        }
+    }
+
+    // The key for this dimension in the map's worlds
+    fn key(&self) -> &'static str {
+        match self {
+            Dimension::Overworld => "overworld",
+            Dimension::Nether => "nether",
+            Dimension::End => "end",
+        }
+    }
+}
+
+// A block position in a dimension, where y is optional
+struct Position {
+    dimension: Dimension,
+    x: i64,
+    y: Option<i64>,
+    z: i64,
+}
+
+impl Position {
+    // The same place in the other dimension, as one block in the Nether is eight in the Overworld
+    fn convert(&self) -> Option<Position> {
+        let (dimension, x, z) = match self.dimension {
+            Dimension::Overworld => (Dimension::Nether, self.x.div_euclid(8), self.z.div_euclid(8)),
+            Dimension::Nether => (Dimension::Overworld, self.x * 8, self.z * 8),
+            Dimension::End => return None,
+        };
+        Some(Position { dimension, x, y: self.y, z })
+    }
+
+    fn coords(&self) -> String {
+        match self.y {
+            Some(y) => format!("{}, {}, {}", self.x, y, self.z),
+            None => format!("{}, {}", self.x, self.z),
+        }
+    }
+
+    // Which chunk and region file the position is in, and where in the chunk it is
+    fn describe(&self) -> String {
+        let (chunk_x, chunk_z) = (self.x.div_euclid(16), self.z.div_euclid(16));
+        format!("{} {}: chunk {}, {} (block {}, {} in the chunk), region file `r.{}.{}.mca`",
+            self.dimension.name(), self.coords(),
+            chunk_x, chunk_z,
+            self.x.rem_euclid(16), self.z.rem_euclid(16),
+            chunk_x.div_euclid(32), chunk_z.div_euclid(32))
+    }
+
+    // A link to the position on the web map, if one is set up
+    fn link(&self, map: &MapConfig) -> Option<String> {
+        let url = map.url.trim_end_matches('/');
+        let world = map.worlds.get(self.dimension.key())?;
+        if url.is_empty() {
+            return None;
+        }
+        let y = self.y.unwrap_or(64);
+        match map.kind.to_lowercase().as_str() {
+            "dynmap" => Some(format!("{}/?worldname={}&mapname=flat&zoom={}&x={}&y={}&z={}", url, world, map.zoom, self.x, y, self.z)),
+            "bluemap" => Some(format!("{}/#{}:{}:{}:{}:150:0:0:0:0:perspective", url, world, self.x, y, self.z)),
+            "squaremap" => Some(format!("{}/?world={}&zoom={}&x={}&z={}", url, world, map.zoom, self.x, self.z)),
+            _ => None,
+        }
+    }
+}
+
+// Read the coordinates and dimension from something like "100 64 -200 nether" or "100, -200"
+fn parse_position(input: &str) -> Result<Position, String> {
+    let mut dimension = Dimension::Overworld;
+    let mut numbers = Vec::new();
+    for word in input.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty()) {
+        if let Some(d) = Dimension::from_name(word) {
+            dimension = d;
+            continue;
+        }
+        let value = match parse_amount(word, None) {
+            Ok((value, "")) => value,
+            _ => return Err(format!("`{}` is not a coordinate", word)),
+        };
+        if value.abs() > WORLD_LIMIT {
+            return Err(format!("{} is outside of the world border", value));
+        }
+        numbers.push(value.floor() as i64);
+    }
+    match numbers[..] {
+        [x, z] => Ok(Position { dimension, x, y: None, z }),
+        [x, y, z] => Ok(Position { dimension, x, y: Some(y), z }),
+        _ => Err("Give either the x and z coordinates, or x, y and z".to_string()),
+    }
+}
+
+pub struct McCoordsCommand;
+
+#[async_trait]
+impl Command for McCoordsCommand {
+    fn name(&self) -> &'static str {
+        "mccoords"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <x> (y) <z> (overworld|nether|end)"
+    }
+    fn about(&self) -> &'static str {
+        "Converts Minecraft coordinates between the Overworld and the Nether, and shows which chunk and region file they are in."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mccoords".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        if input.trim().is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        let position = parse_position(&input).map_err(CommandError::BadUsage)?;
+        let map = config.read().unwrap().map.clone();
+        let mut out = Vec::new();
+        let mut positions = vec![position];
+        match positions[0].convert() {
+            Some(converted) => {
+                out.push(format!("{} {} is {} {}",
+                    positions[0].dimension.name(), positions[0].coords(), converted.dimension.name(), converted.coords()));
+                positions.push(converted);
+            },
+            None => out.push("The End has no matching coordinates in other dimensions".to_string()),
+        }
+        for position in &positions {
+            out.push(position.describe());
+        }
+        for position in &positions {
+            if let Some(link) = position.link(&map) {
+                // Keep Discord from embedding every link
+                out.push(format!("{} map: <{}>", position.dimension.name(), link));
+            }
+        }
+        Ok(out.join("\n"))
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::{Arc, RwLock};
+
+use serenity::async_trait;
+
+use crate::config::{Config, Role};
+use crate::commands::{Command, CommandError};
+use crate::util::format::{ansi, component_warnings, parse, parse_component, plain};
+use crate::util::snbt;
+use crate::util::perms::check_permission;
+
+// Replies are cut off after this many characters, to stay within Discord's limits
+const MAX_REPLY: usize = 1900;
+
+// Take the text out of a code block if it was put in one, so Discord leaves it alone
+fn unwrap_code(input: &str) -> &str {
+    let text = input.trim();
+    match text.strip_prefix("```").and_then(|t| t.strip_suffix("```")) {
+        // Skip the language after the opening backticks
+        Some(code) => code.split_once('\n').map_or(code, |(first, rest)| if first.contains(char::is_whitespace) { code } else { rest }).trim(),
+        None => text.trim_matches('`').trim(),
+    }
+}
+
+// Show a line of the input with an arrow under the character at a line and column, both counted from 1
+fn pointer(input: &str, line: usize, column: usize) -> String {
+    let text = input.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+    // Only show the part around the mistake for long lines
+    let start = column.saturating_sub(40);
+    let shown: String = text.chars().skip(start).take(80).collect();
+    format!("```\n{}\n{}^\n```\n", shown.replace("```", "`\u{200b}``"), " ".repeat(column.saturating_sub(start + 1)))
+}
+
+// Find the line and column of a character in the input, counted from 1
+fn line_column(input: &str, position: usize) -> (usize, usize) {
+    let before: String = input.chars().take(position.saturating_sub(1)).collect();
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+// Put together the pretty printed text, a preview and any warnings, leaving out the pretty text if it doesn't fit
+fn checked_reply(title: &str, pretty: &str, language: &str, preview: Option<String>, warnings: &[String]) -> String {
+    let preview = preview.map_or(String::new(), |p| format!("Preview:\n```ansi\n{}\n```\n", p));
+    let warnings = if warnings.is_empty() {
+        String::new()
+    } else {
+        format!("Warnings:\n{}", warnings.iter().map(|w| format!("- {}", w)).collect::<Vec<String>>().join("\n"))
+    };
+    let pretty = format!("```{}\n{}\n```\n", language, pretty.replace("```", "`\u{200b}``"));
+    let reply = format!("{}\n{}{}{}", title, pretty, preview, warnings);
+    if reply.chars().count() <= MAX_REPLY {
+        return reply;
+    }
+    let reply = format!("{} (too long to show formatted)\n{}{}", title, preview, warnings);
+    if reply.chars().count() <= MAX_REPLY {
+        return reply;
+    }
+    // The escape codes of a heavily styled preview can make it too long on its own
+    let reply = format!("{} (too long to show formatted or preview)\n{}", title, warnings);
+    reply.chars().take(MAX_REPLY).collect()
+}
+
+pub struct McFormatCommand;
+
+#[async_trait]
+impl Command for McFormatCommand {
+    fn name(&self) -> &'static str {
+        "mcformat"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <text with & or § codes, or a JSON text component>"
+    }
+    fn about(&self) -> &'static str {
+        "Shows what Minecraft formatting codes or a JSON text component look like, and the text without formatting."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcformat".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let text = unwrap_code(&input);
+        if text.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        let spans = parse(text);
+        let plain = plain(&spans);
+        if plain.trim().is_empty() {
+            return Err(CommandError::BadUsage("There is no text to show".to_string()));
+        }
+        // Keep the reply within Discord's limits, counting the escape codes for the colours as well
+        let reply = format!("```ansi\n{}\n```\nPlain text: `{}`", ansi(&spans), plain.replace('`', "'"));
+        if reply.chars().count() > MAX_REPLY {
+            return Err(CommandError::BadUsage("That text is too long to show".to_string()));
+        }
+        Ok(reply)
+    }
+}
+
+pub struct McJsonCommand;
+
+#[async_trait]
+impl Command for McJsonCommand {
+    fn name(&self) -> &'static str {
+        "mcjson"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <JSON text component>"
+    }
+    fn about(&self) -> &'static str {
+        "Checks a JSON text component for mistakes, and shows it formatted with a preview."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcjson".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let text = unwrap_code(&input);
+        if text.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        let component: serde_json::Value = match serde_json::from_str(text) {
+            Ok(c) => c,
+            Err(e) => {
+                // Serde counts columns in bytes
+                let line = text.lines().nth(e.line().saturating_sub(1)).unwrap_or_default();
+                let column = line.get(..e.column().min(line.len())).map_or(e.column(), |l| l.chars().count());
+                return Err(CommandError::BadUsage(format!("That isn't valid JSON, at line {} column {}:\n{}{}",
+                    e.line(), column, pointer(text, e.line(), column), e.to_string().split(" at line").next().unwrap_or_default())));
+            },
+        };
+        let mut warnings = Vec::new();
+        component_warnings(&component, "$", &mut warnings);
+        let spans = parse_component(&component);
+        let preview = if plain(&spans).trim().is_empty() { None } else { Some(ansi(&spans)) };
+        let pretty = serde_json::to_string_pretty(&component).unwrap_or_default();
+        let title = if warnings.is_empty() { "That's a valid text component!" } else { "That's valid JSON, but it has some mistakes" };
+        Ok(checked_reply(title, &pretty, "json", preview, &warnings))
+    }
+}
+
+pub struct McSnbtCommand;
+
+#[async_trait]
+impl Command for McSnbtCommand {
+    fn name(&self) -> &'static str {
+        "mcsnbt"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <SNBT>"
+    }
+    fn about(&self) -> &'static str {
+        "Checks SNBT, like the data in give or data commands, for mistakes and shows it formatted, with a preview if it is a text component."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcsnbt".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let text = unwrap_code(&input);
+        if text.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        let tag = match snbt::parse(text) {
+            Ok(t) => t,
+            Err(e) => {
+                let (line, column) = line_column(text, e.position);
+                return Err(CommandError::BadUsage(format!("That isn't valid SNBT, at line {} column {}:\n{}{}",
+                    line, column, pointer(text, line, column), e.message)));
+            },
+        };
+        // Text components can be written as SNBT since 1.21.5
+        let json = tag.to_json();
+        let mut preview = None;
+        let mut warnings = Vec::new();
+        if matches!(&tag, snbt::Tag::Compound(c) if c.iter().any(|(k, _t)| k == "text" || k == "translate")) {
+            component_warnings(&json, "$", &mut warnings);
+            preview = Some(ansi(&parse_component(&json)));
+        }
+        Ok(checked_reply("That's valid SNBT!", &tag.pretty(0), "", preview, &warnings))
+    }
+}
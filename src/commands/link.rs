@@ -0,0 +1,190 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use serenity::async_trait;
+use serenity::client::Context;
+use serenity::model::channel::Message;
+use serenity::model::id::UserId;
+use serenity::utils::parse_username;
+
+use crate::config::{Config, Role};
+use crate::commands::{Command, CommandError};
+use crate::storage::{LinkCode, Storage};
+use crate::util::bridge::bridged_player;
+use crate::util::duration::format_duration;
+use crate::util::format::strip_codes;
+use crate::util::links::{linked_player, linked_user, new_code};
+use crate::util::perms::check_permission;
+use crate::util::rcon::run_command;
+use crate::util::uuid::valid_name;
+
+pub struct LinkCommand {
+    pub storage: Arc<RwLock<Storage>>,
+}
+
+impl LinkCommand {
+    // Send a player a new code in game over RCON, replacing any code they were sent before
+    async fn send_code(&self, config: &Arc<RwLock<Config>>, player: &str, user: u64) -> Result<(), CommandError> {
+        let (rcon, linking) = {
+            let config = config.read().unwrap();
+            (config.rcon.clone(), config.linking.clone())
+        };
+        if rcon.password.is_empty() {
+            return Err(CommandError::BadUsage("RCON needs to be set up to send link codes".to_string()));
+        }
+
+        let code = new_code();
+        let command = linking.command.replace("%player%", player).replace("%code%", &code);
+        match run_command(&rcon, &command).await {
+            // Minecraft answers instead of sending the message if the player isn't online
+            Ok(output) if strip_codes(&output).contains("No player was found") =>
+                Err(CommandError::BadUsage(format!("{} needs to be online in Minecraft to get a code", player))),
+            Ok(_) => {
+                let now = Utc::now().timestamp();
+                let mut storage = self.storage.write().unwrap();
+                storage.link_codes.retain(|c| c.expires > now && !c.player.eq_ignore_ascii_case(player));
+                storage.link_codes.push(LinkCode { code, player: player.to_string(), user, expires: now + linking.expiry });
+                storage.save();
+                Ok(())
+            },
+            Err(e) => Err(CommandError::BadUsage(e.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl Command for LinkCommand {
+    fn name(&self) -> &'static str {
+        "link"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <Minecraft name|code>"
+    }
+    fn about(&self) -> &'static str {
+        "Links a Minecraft player to your Discord account, by sending the player a code in game to send back here"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, _input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.link".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        Err(CommandError::BadUsage("Accounts can only be linked from Discord or Minecraft".to_string()))
+    }
+    async fn execute_message(&self, _ctx: &Context, msg: &Message, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.link".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let input = input.trim();
+        let (prefix, expiry, bridged) = {
+            let config = config.read().unwrap();
+            (config.prefix.to_string(), config.linking.expiry, bridged_player(&config, msg))
+        };
+
+        // Players can ask for a code in game, which only they can see
+        if let Some(b) = bridged {
+            self.send_code(&config, &b.player, 0).await?;
+            return Ok(format!("{}, I sent you a code in game. Send {}link <code> on Discord within {} to link your account",
+                b.player, prefix, format_duration(expiry)));
+        }
+
+        let user = *msg.author.id.as_u64();
+        if input.is_empty() {
+            return match linked_player(&config.read().unwrap(), &self.storage.read().unwrap(), user) {
+                Some(player) => Ok(format!("Your Discord account is linked to {}", player)),
+                None => Err(CommandError::InvalidSyntax(self.usage().to_string())),
+            };
+        }
+
+        // A code that was sent to a player, for this user or asked for in game
+        let linked = {
+            let now = Utc::now().timestamp();
+            let mut storage = self.storage.write().unwrap();
+            storage.link_codes.retain(|c| c.expires > now);
+            match storage.link_codes.iter().position(|c| c.code.eq_ignore_ascii_case(input) && (c.user == 0 || c.user == user)) {
+                Some(i) => {
+                    let code = storage.link_codes.remove(i);
+                    // A Discord account is linked to one player, and a player to one account
+                    storage.links.retain(|player, id| *id != user && !player.eq_ignore_ascii_case(&code.player));
+                    storage.links.insert(code.player.to_string(), user);
+                    storage.save();
+                    Some(code.player)
+                },
+                None => None,
+            }
+        };
+        if let Some(player) = linked {
+            return Ok(format!("Linked your Discord account to {}", player));
+        }
+
+        if !valid_name(input) {
+            return Err(CommandError::BadUsage(format!("That code is wrong or has expired, send {}link <Minecraft name> to get a new one", prefix)));
+        }
+        if linked_user(&config.read().unwrap(), &self.storage.read().unwrap(), input) == Some(user) {
+            return Ok(format!("Your Discord account is already linked to {}", input));
+        }
+        self.send_code(&config, input, user).await?;
+        Ok(format!("I sent a code to {} in game. Send {}link <code> here within {} to finish linking", input, prefix, format_duration(expiry)))
+    }
+}
+
+pub struct WhoisCommand {
+    pub storage: Arc<RwLock<Storage>>,
+}
+
+impl WhoisCommand {
+    // Find who is linked to a player or Discord user, using the context to show Discord names if there is one
+    async fn lookup(&self, ctx: Option<&Context>, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.whois".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+        let (user, player) = {
+            let (config, storage) = (config.read().unwrap(), self.storage.read().unwrap());
+            // Player names can be numbers too, so a linked player comes before a user ID
+            match (parse_username(input), linked_user(&config, &storage, input)) {
+                (Some(user), _) => (Some(user), linked_player(&config, &storage, user)),
+                (None, Some(user)) => (Some(user), Some(input.to_string())),
+                (None, None) => match input.parse::<u64>() {
+                    Ok(user) => (Some(user), linked_player(&config, &storage, user)),
+                    Err(_) => (None, None),
+                },
+            }
+        };
+
+        let name = match (ctx, user) {
+            (Some(ctx), Some(user)) => match UserId(user).to_user(ctx).await {
+                Ok(u) => u.name,
+                Err(_) => user.to_string(),
+            },
+            (_, Some(user)) => user.to_string(),
+            (_, None) => String::new(),
+        };
+        match (user, player) {
+            (Some(_), Some(player)) if player.eq_ignore_ascii_case(input) => Ok(format!("{} is linked to Discord user {}", player, name)),
+            (Some(_), Some(player)) => Ok(format!("Discord user {} is linked to {}", name, player)),
+            (Some(_), None) => Err(CommandError::BadUsage(format!("Discord user {} isn't linked to a Minecraft player", name))),
+            (None, _) => Err(CommandError::BadUsage(format!("{} isn't linked to a Discord account", input))),
+        }
+    }
+}
+
+#[async_trait]
+impl Command for WhoisCommand {
+    fn name(&self) -> &'static str {
+        "whois"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <Minecraft name|@user>"
+    }
+    fn about(&self) -> &'static str {
+        "Shows the Discord account a Minecraft player is linked to, or the other way around"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        self.lookup(None, config, role, input).await
+    }
+    async fn execute_message(&self, ctx: &Context, _msg: &Message, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        self.lookup(Some(ctx), config, role, input).await
+    }
+}
@@ -1,192 +1,943 @@
 use std::sync::{Arc, RwLock};
 
+use serenity::async_trait;
+
 use crate::config::{Config, Role};
 use crate::commands::{Command, CommandError};
+use crate::util::anvil::{cheapest_order, enchantments, prior_work_penalty, Enchantment, Piece, TOO_EXPENSIVE};
+use crate::util::containers::{breakdown, find_container, units};
+use crate::util::duration::{format_duration, parse_duration};
+use crate::util::expression::{parse_amount, parse_count};
+use crate::util::items::{closest, find_item, normalise};
 use crate::util::perms::check_permission;
+use crate::util::recipes::{display_name, materials, recipes};
+use crate::util::slime::{is_slime_chunk, world_seed};
+use crate::util::uuid::{dashed, int_array, offline_uuid, parse_uuid, valid_name};
+
+// An item named in a command, with how many fit in a stack
+struct NamedItem {
+    name: String,
+    stack: u32,
+    // Whether the item is in the item table, otherwise a stack of 64 is assumed
+    known: bool,
+}
+
+impl NamedItem {
+    // Find the item for a name, or None if no name was given
+    fn find(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        Some(match find_item(name) {
+            Some(item) => NamedItem { name: item.name.to_string(), stack: item.stack, known: true },
+            None => NamedItem { name: name.to_string(), stack: 64, known: false },
+        })
+    }
+
+    // Note to add to a reply when the item wasn't found
+    fn note(&self) -> &'static str {
+        if self.known { "" } else { " (I don't know this item, so I assumed it stacks to 64)" }
+    }
+}
+
+// Read an amount of items and the item named after it, where units like stacks use the item's stack size
+fn count_of_item(input: &str, usage: &str) -> Result<(u64, Option<NamedItem>), CommandError> {
+    if input.trim().is_empty() {
+        return Err(CommandError::InvalidSyntax(usage.to_string()));
+    }
+    // The item comes after the amount, so read it once to find the item and again with its stack size
+    let (_count, name) = parse_count(input, Some(64)).map_err(CommandError::BadUsage)?;
+    let item = NamedItem::find(name);
+    let (count, _name) = parse_count(input, Some(item.as_ref().map_or(64, |i| i.stack as u64))).map_err(CommandError::BadUsage)?;
+    Ok((count, item))
+}
+
+// Read an amount that isn't items, like stacks or shulkers, which can be a decimal but not negative
+fn positive_amount<'a>(input: &'a str, usage: &str, stack: Option<u64>) -> Result<(f64, &'a str), CommandError> {
+    if input.trim().is_empty() {
+        return Err(CommandError::InvalidSyntax(usage.to_string()));
+    }
+    let (amount, rest) = parse_amount(input, stack).map_err(CommandError::BadUsage)?;
+    if amount < 0.0 {
+        return Err(CommandError::BadUsage("The amount can't be negative".to_string()));
+    }
+    Ok((amount, rest))
+}
 
 pub struct McItemsCommand;
 
+#[async_trait]
 impl Command for McItemsCommand {
     fn name(&self) -> &'static str {
         "mcitems"
     }
     fn usage(&self) -> &'static str {
-        "Usage: <number of stacks>"
+        "Usage: <number of stacks> (item)"
     }
     fn about(&self) -> &'static str {
-        "Converts a given amount of Minecraft stacks (64) to the item count."
-    }
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
-        if check_permission(&config.read().unwrap(), "cmd.mcitems".to_string(), role) {
-            // Get all the numbers (and periods) in the message
-            let stacks = input.chars().filter(|c| c.is_digit(10) || c == &'.').collect::<String>();
-            // Check if numbers were found
-            if !stacks.is_empty() {
-                // Try to parse into float
-                if let Some(s) = stacks.parse::<f32>().ok() {
-                    if s.is_finite() {
-                        // Multiply by 32
-                        let items: f32 = s * 64.0;
-                        // Format string
-                        return Ok(format!("{} stack{} break{} down into {:.0} item{}",
-                        // Replace stack count
-                            s,
-                        // Make stacks plural if needed
-                        if s == 1.0 { "" } else { "s" },
-                        // Make verb plural if needed
-                        if s != 1.0 { "" } else { "s" },
-                        // Replace item output
-                        items,
-                        // Make items plural if needed
-                        if items == 1.0 { "" } else { "s"}));
-                    } else {
-                        // If user inputs ridiculously high number
-                        return Err(CommandError::BadUsage("I dunno lol".to_string()));
-                    }
-                }
-            }
-            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
-        } else {
-            return Err(CommandError::NoPerms)
+        "Converts a given amount of Minecraft stacks (64, or the stack size of the given item) to the item count."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcitems".to_string(), role) {
+            return Err(CommandError::NoPerms);
         }
+
+        // A stack is one stack here, so "1 sb" is 27 stacks
+        let (stacks, name) = positive_amount(&input, self.usage(), Some(1))?;
+        let item = NamedItem::find(name);
+        // Multiply by the stack size
+        let items = stacks * item.as_ref().map_or(64, |i| i.stack) as f64;
+        Ok(format!("{} stack{}{} break{} down into {:.0} item{}{}",
+            stacks,
+            if stacks == 1.0 { "" } else { "s" },
+            // Name the item if one was given
+            item.as_ref().map_or(String::new(), |i| format!(" of {} ({} per stack)", i.name, i.stack)),
+            if stacks != 1.0 { "" } else { "s" },
+            items,
+            if items == 1.0 { "" } else { "s" },
+            item.as_ref().map_or("", |i| i.note())))
     }
 }
 
 pub struct McStacksCommand;
 
+#[async_trait]
 impl Command for McStacksCommand {
     fn name(&self) -> &'static str {
         "mcstacks"
     }
     fn usage(&self) -> &'static str {
-        "Usage: <number of items>"
+        "Usage: <number of items> (item)"
     }
     fn about(&self) -> &'static str {
-        "Converts a given amount of Minecraft items to how many stacks (64) they make up, with the remainder."
-    }
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
-        if check_permission(&config.read().unwrap(), "cmd.mcstacks".to_string(), role) {
-            // Get all the numbers in the message
-            let items = input.chars().filter(|c| c.is_digit(10)).collect::<String>();
-            if !items.is_empty() {
-                if let Some(items) = items.parse::<i32>().ok() {
-                    // Get the amount of stacks
-                    let stacks: i32 = items / 64;
-                    // Get the remainder
-                    let left: i32 = items % 64;
-                    // Format string
-                    return Ok(format!("{} item{} break{} down into {} stack{} with {} item{} left over", 
-                    // Replace item count
-                    items,
-                    // Make items plural if needed
-                    if items == 1 { "" } else { "s" },
-                    // Make verb plural if needed
-                    if items != 1 { "" } else { "s" },
-                    // Replace stacks count
-                    stacks,
-                    // Make stacks plural if needed
-                    if stacks == 1 { "" } else { "s" },
-                    // Replace remainder
-                    left,
-                    // Make items plural if needed
-                    if left == 1 { "" } else { "s" }));
-                }
-            }
-            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
-        } else {
+        "Converts a given amount of Minecraft items to how many stacks (64, or the stack size of the given item) they make up, with the remainder."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcstacks".to_string(), role) {
             return Err(CommandError::NoPerms);
         }
+
+        let (items, item) = count_of_item(&input, self.usage())?;
+        let stack = item.as_ref().map_or(64, |i| i.stack as u64);
+        if stack == 1 {
+            let name = item.as_ref().map_or("", |i| &i.name);
+            return Ok(format!("{} can't be stacked, so {} item{} take{} up {} slot{}",
+                name, items, pluralize(items), third_person_ending(items), items, pluralize(items)));
+        }
+        // Get the amount of stacks
+        let stacks = items / stack;
+        // Get the remainder
+        let left = items % stack;
+        Ok(format!("{}{} item{} break{} down into {} stack{} with {} item{} left over{}",
+            items,
+            // Name the item if one was given
+            item.as_ref().map_or(String::new(), |i| format!(" {}", i.name)),
+            pluralize(items),
+            third_person_ending(items),
+            stacks,
+            pluralize(stacks),
+            left,
+            pluralize(left),
+            // Mention the stack size if it isn't the usual 64
+            match &item {
+                Some(i) if i.stack != 64 => format!(" (stacks of {})", i.stack),
+                Some(i) => i.note().to_string(),
+                None => String::new(),
+            }))
     }
 }
 
 pub struct McShulkersCommand;
 
+#[async_trait]
 impl Command for McShulkersCommand {
     fn name(&self) -> &'static str {
         "mcshulkers"
     }
     fn usage(&self) -> &'static str {
-        "Usage: <number of items>"
+        "Usage: <number of items> (item)"
     }
     fn about(&self) -> &'static str {
         "Converts a given amount of Minecraft items to how many shulkers, stacks. and remaining items they make up."
     }
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
         if !check_permission(&config.read().unwrap(), "cmd.mcshulkers".to_string(), role) {
             return Err(CommandError::NoPerms);
         }
  
-        // Get all the numbers in the message
-        let items = input.chars().filter(|c| c.is_digit(10)).collect::<String>();
-        if !items.is_empty() {
-            if let Some(items) = items.parse::<i32>().ok() {
-                let shulkers: i32 = items / 1728;
-                let stacks: i32 = (items % 1728) / 64;
-                let left: i32 = (items % 1728) % 64;
-
-                return Ok(format!(
-                    "{} item{} break{} down into {} shulker{} plus {} stack{} and {} item{} left over", 
-                    items,
-                    pluralize(items),
-                    third_person_ending(items),
-                    shulkers,
-                    pluralize(shulkers),
-                    stacks,
-                    pluralize(stacks),
-                    left,
-                    pluralize(left)));
-            }
-        }
-        return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        let (items, item) = count_of_item(&input, self.usage())?;
+        into_container(&config.read().unwrap(), items, item.as_ref(), "shulker")
     }
 }
 
 pub struct McUnshulkerCommand;
 
+#[async_trait]
 impl Command for McUnshulkerCommand {
     fn name(&self) -> &'static str {
         "mcunshulker"
     }
     fn usage(&self) -> &'static str {
-        "Usage: <number of shulkers>"
+        "Usage: <number of shulkers> (item)"
     }
     fn about(&self) -> &'static str {
         "Converts a given amount of Minecraft shulker (optionally with decimalss) to number of items."
     }
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
         if !check_permission(&config.read().unwrap(), "cmd.mcunshulker".to_string(), role) {
             return Err(CommandError::NoPerms);
         }
  
-        // Get all the numbers in the message
-        let shulkers = input.chars().filter(|c| c.is_digit(10) || c == &'.').collect::<String>();
-        if !shulkers.is_empty() {
-            if let Some(s) = shulkers.parse::<f32>().ok() {
-                if !s.is_finite() {
-                    // If user inputs ridiculously high number
-                    return Err(CommandError::BadUsage("I dunno lol".to_string()));
+        let (shulkers, name) = positive_amount(&input, self.usage(), None)?;
+        let item = NamedItem::find(name);
+        out_of_container(&config.read().unwrap(), shulkers, item.as_ref(), "shulker")
+    }
+}
+
+// Break an amount of items down into a container, the stacks in it and the items left over
+fn into_container(config: &Config, items: u64, item: Option<&NamedItem>, container: &str) -> Result<String, CommandError> {
+    let stack = item.map_or(64, |i| i.stack as u64);
+    let units = match units(config, container, stack) {
+        Some(u) => u,
+        None => return Err(CommandError::BadUsage(format!("There is no container called {} set up", container.replace('_', " ")))),
+    };
+    Ok(format!("{}{} item{} break{} down into {}{}",
+        items,
+        item.map_or(String::new(), |i| format!(" {}", i.name)),
+        if items == 1 { "" } else { "s" },
+        if items != 1 { "" } else { "s" },
+        breakdown(items, &units),
+        stack_note(item)))
+}
+
+// Note to add to a reply about the size of the item's stacks, if it isn't the usual 64
+fn stack_note(item: Option<&NamedItem>) -> String {
+    match item {
+        Some(i) if i.stack == 1 => " (can't be stacked)".to_string(),
+        Some(i) if i.stack != 64 => format!(" (stacks of {})", i.stack),
+        Some(i) => i.note().to_string(),
+        None => String::new(),
+    }
+}
+
+// Work out how many items fit in an amount of containers, which can be a decimal
+fn out_of_container(config: &Config, amount: f64, item: Option<&NamedItem>, container: &str) -> Result<String, CommandError> {
+    let stack = item.map_or(64, |i| i.stack as u64);
+    let size = match units(config, container, stack).and_then(|u| u.first().map(|u| u.items)) {
+        Some(s) => s,
+        None => return Err(CommandError::BadUsage(format!("There is no container called {} set up", container.replace('_', " ")))),
+    };
+    let items = amount * size as f64;
+    if items > 9007199254740992.0 {
+        return Err(CommandError::BadUsage("That number is too large".to_string()));
+    }
+    Ok(format!("{} {}{}{} break{} down into {:.0} item{}{}",
+        amount,
+        container.replace('_', " "),
+        if amount == 1.0 { "" } else { "s" },
+        item.map_or(String::new(), |i| format!(" of {} ({} per stack)", i.name, i.stack)),
+        if amount != 1.0 { "" } else { "s" },
+        items,
+        if items == 1.0 { "" } else { "s" },
+        item.map_or("", |i| i.note())))
+}
+
+pub struct McContainersCommand;
+
+#[async_trait]
+impl Command for McContainersCommand {
+    fn name(&self) -> &'static str {
+        "mccontainers"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <number of items> (item) (container) or <number of containers> <container> (item)"
+    }
+    fn about(&self) -> &'static str {
+        "Converts a given amount of Minecraft items to how many containers, like chests or shulkers, they fill, or an amount of containers to the items in them."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mccontainers".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+
+        let config = config.read().unwrap();
+        // A container at the end after an item, like "300 pearls chest" or "2 shulkers 10 pearls chest", fills that container
+        let (_items, rest) = positive_amount(&input, self.usage(), Some(64))?;
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let at_end = (0..words.len()).find_map(|i| find_container(&config, &words[i..].join(" ")).map(|(c, _c)| (i, c)));
+
+        // Otherwise if a container comes right after the amount, like "3 chests of pearls", count the items in them
+        if !matches!(at_end, Some((i, _c)) if i > 0) {
+            // Containers are named in full here, so read the amount without units
+            let (amount, rest) = positive_amount(&input, self.usage(), None)?;
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            for i in (1..=words.len()).rev() {
+                if let Some((container, _c)) = find_container(&config, &words[..i].join(" ")) {
+                    let name = words[i..].join(" ");
+                    let item = NamedItem::find(name.strip_prefix("of ").unwrap_or(&name));
+                    return out_of_container(&config, amount, item.as_ref(), container);
+                }
+            }
+        }
+
+        let (container, name) = match at_end {
+            Some((i, container)) => (Some(container), words[..i].join(" ")),
+            None => (None, rest.to_string()),
+        };
+        let item = NamedItem::find(&name);
+        let stack = item.as_ref().map_or(64, |i| i.stack as u64);
+        let (items, _rest) = parse_count(&input, Some(stack)).map_err(CommandError::BadUsage)?;
+        if let Some(container) = container {
+            return into_container(&config, items, item.as_ref(), container);
+        }
+
+        // Without a container, break the items down into every container
+        let mut out = vec![format!("{}{} item{} break{} down into:",
+            items,
+            item.as_ref().map_or(String::new(), |i| format!(" {}", i.name)),
+            if items == 1 { "" } else { "s" },
+            if items != 1 { "" } else { "s" })];
+        for container in config.containers.keys() {
+            if let Some(u) = units(&config, container, stack) {
+                out.push(format!("- {}", breakdown(items, &u)));
+            }
+        }
+        let note = stack_note(item.as_ref());
+        if !note.is_empty() {
+            out.push(note.trim().to_string());
+        }
+        Ok(out.join("\n"))
+    }
+}
+
+// The highest level whose total points still fit in the game's experience counter
+const MAX_LEVEL: u64 = 21863;
+
+// Average experience from things players commonly use to level up
+const XP_SOURCES: [(&str, f64); 4] = [
+    ("bottles o' enchanting", 7.0),
+    ("nether quartz ores", 3.5),
+    ("diamond ores", 5.0),
+    ("coal ores", 1.0),
+];
+
+// The total amount of points needed to reach a level from 0
+fn level_points(level: u64) -> u64 {
+    match level {
+        0..=16 => level * level + 6 * level,
+        17..=31 => (5 * level * level - 81 * level) / 2 + 360,
+        _ => (9 * level * level + 4440 - 325 * level) / 2,
+    }
+}
+
+// The level reached with an amount of points, and the points towards the next level
+fn points_level(points: u64) -> (u64, u64) {
+    let mut level = 0;
+    while level < MAX_LEVEL && level_points(level + 1) <= points {
+        level += 1;
+    }
+    (level, points - level_points(level))
+}
+
+// How many of each source of experience it takes on average to get an amount of points
+fn xp_sources(points: u64) -> String {
+    let sources: Vec<String> = XP_SOURCES.iter()
+        .map(|(name, average)| format!("{:.0} {}", (points as f64 / average).ceil(), name))
+        .collect();
+    format!("That's about {} on average.", sources.join(", "))
+}
+
+pub struct McXpCommand;
+
+impl McXpCommand {
+    fn level(&self, input: &str) -> Result<u64, CommandError> {
+        let (level, rest) = parse_count(input, None).map_err(CommandError::BadUsage)?;
+        if !rest.is_empty() && !["l", "lvl", "level", "levels"].contains(&rest.to_lowercase().as_str()) {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+        if level > MAX_LEVEL {
+            return Err(CommandError::BadUsage(format!("Levels above {} can't be reached", MAX_LEVEL)));
+        }
+        Ok(level)
+    }
+}
+
+#[async_trait]
+impl Command for McXpCommand {
+    fn name(&self) -> &'static str {
+        "mcxp"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <level>, <level> to <level> or <number of points> points"
+    }
+    fn about(&self) -> &'static str {
+        "Converts between Minecraft experience levels and points, and works out the points needed to go from one level to another."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcxp".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        // A range of levels, like "10 to 30" or "10..30", as a plain - would be read as subtracting
+        let range = input.split_once(" to ")
+            .or_else(|| input.split_once("->"))
+            .or_else(|| input.split_once(".."));
+        if let Some((from, to)) = range {
+            let (from, to) = (self.level(from)?, self.level(to)?);
+            if to < from {
+                return Err(CommandError::BadUsage("The second level has to be higher than the first".to_string()));
+            }
+            let points = level_points(to) - level_points(from);
+            return Ok(format!("Going from level {} to {} takes {} point{}\n{}",
+                from, to, points, pluralize(points), xp_sources(points)));
+        }
+
+        // An amount of points, like "1000 points" or "1000xp"
+        let (amount, rest) = parse_count(&input, None).map_err(CommandError::BadUsage)?;
+        if ["p", "pts", "point", "points", "xp"].contains(&rest) {
+            if amount > level_points(MAX_LEVEL) {
+                return Err(CommandError::BadUsage("That's more points than the game can hold".to_string()));
+            }
+            let (level, left) = points_level(amount);
+            return Ok(format!("{} point{} is level {} with {}/{} points towards level {}",
+                amount, pluralize(amount), level, left, level_points(level + 1) - level_points(level), level + 1));
+        }
+
+        let level = self.level(&input)?;
+        let points = level_points(level);
+        // Dying drops 7 points per level, up to 100, and the rest is lost
+        let dropped = (level * 7).min(100).min(points);
+        Ok(format!("Level {} takes {} point{} from level 0, and {} more to reach level {}\n{}\nDying at level {} drops {} point{}, the other {} are lost",
+            level, points, pluralize(points), level_points(level + 1) - points, level + 1,
+            xp_sources(points),
+            level, dropped, pluralize(dropped), points - dropped))
+    }
+}
+
+
+// Ticks in a Minecraft day, which lasts 20 minutes
+const DAY_TICKS: u64 = 24000;
+
+// The names /time set accepts, and some other common ones
+const NAMED_TIMES: [(&str, u64); 6] = [
+    ("day", 1000),
+    ("noon", 6000),
+    ("sunset", 12000),
+    ("night", 13000),
+    ("midnight", 18000),
+    ("sunrise", 23000),
+];
+
+const MOON_PHASES: [&str; 8] = [
+    "full", "waning gibbous", "third quarter", "waning crescent",
+    "new", "waxing crescent", "first quarter", "waxing gibbous",
+];
+
+// Format an amount of real seconds, keeping fractions for short times
+fn real_time(seconds: f64) -> String {
+    if seconds < 60.0 {
+        format!("{} second{}", (seconds * 100.0).round() / 100.0, if seconds == 1.0 { "" } else { "s" })
+    } else {
+        format_duration(seconds.round() as i64)
+    }
+}
+
+// The clock time in the game at a time of day, where 0 is 6:00
+fn clock(daytime: u64) -> String {
+    let daytime = daytime % DAY_TICKS;
+    format!("{:02}:{:02}", (daytime / 1000 + 6) % 24, daytime % 1000 * 60 / 1000)
+}
+
+// Describe a game time like /time query gametime gives, with the day, clock time and moon phase
+fn game_time(ticks: u64) -> String {
+    let day = ticks / DAY_TICKS;
+    format!("As a game time that's {} on day {}, with a {} moon", clock(ticks), day, MOON_PHASES[(day % 8) as usize])
+}
+
+pub struct McTimeCommand;
+
+impl McTimeCommand {
+    // Describe an amount of game ticks, which can also be a time of the game
+    fn ticks(&self, ticks: f64) -> String {
+        let mut out = format!("{} tick{} is {} of real time, {} redstone ticks or {} in-game days",
+            ticks, if ticks == 1.0 { "" } else { "s" },
+            real_time(ticks / 20.0),
+            ticks / 2.0,
+            (ticks / DAY_TICKS as f64 * 1000.0).round() / 1000.0);
+        if ticks.fract() == 0.0 {
+            out.push_str(&format!("\n{}", game_time(ticks as u64)));
+        }
+        out
+    }
+}
+
+#[async_trait]
+impl Command for McTimeCommand {
+    fn name(&self) -> &'static str {
+        "mctime"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <ticks>, <amount> <rt|seconds|minutes|hours|days>, <hh:mm> or <day|noon|sunset|night|midnight|sunrise>"
+    }
+    fn about(&self) -> &'static str {
+        "Converts between Minecraft game ticks, redstone ticks, real time, in-game days and /time set values."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mctime".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        // A named time, like noon
+        if let Some((name, time)) = NAMED_TIMES.iter().find(|(name, _t)| *name == input) {
+            return Ok(format!("`/time set {}` sets the time to {}, which is {} on the clock", name, time, clock(*time)));
+        }
+
+        // A clock time, like 18:30
+        if let Some((hours, minutes)) = input.split_once(':') {
+            return match (hours.parse::<u64>(), minutes.parse::<u64>()) {
+                (Ok(h), Ok(m)) if h < 24 && m < 60 => {
+                    // The day starts at 6:00 in Minecraft
+                    let time = ((h + 18) % 24) * 1000 + m * 1000 / 60;
+                    Ok(format!("{:02}:{:02} is `/time set {}`", h, m, time))
+                },
+                _ => Err(CommandError::BadUsage(format!("{} is not a time on the clock", input))),
+            };
+        }
+
+        // Real time written like 1h30m, with at least two units as a single unit like 5d is read below, where d means in-game days
+        let units = input.split(|c: char| c.is_ascii_digit()).filter(|u| !u.is_empty()).count();
+        if let Some(seconds) = parse_duration(&input).filter(|_| units >= 2) {
+            let ticks = seconds as f64 * 20.0;
+            return Ok(format!("{} of real time is {} ticks, or {} in-game days",
+                format_duration(seconds), ticks, (ticks / DAY_TICKS as f64 * 1000.0).round() / 1000.0));
+        }
+
+        let (amount, unit) = parse_amount(&input, None).map_err(CommandError::BadUsage)?;
+        if amount < 0.0 {
+            return Err(CommandError::BadUsage("Time can't be negative".to_string()));
+        }
+        match unit {
+            "" | "t" | "gt" | "tick" | "ticks" | "game ticks" => Ok(self.ticks(amount)),
+            "rt" | "redstone tick" | "redstone ticks" => Ok(format!("{} redstone tick{} is {} game ticks, or {} of real time",
+                amount, if amount == 1.0 { "" } else { "s" }, amount * 2.0, real_time(amount / 10.0))),
+            "s" | "sec" | "secs" | "second" | "seconds" => Ok(self.ticks(amount * 20.0)),
+            "min" | "mins" | "minute" | "minutes" => Ok(self.ticks(amount * 1200.0)),
+            "h" | "hour" | "hours" => Ok(self.ticks(amount * 72000.0)),
+            "d" | "day" | "days" => {
+                let ticks = amount * DAY_TICKS as f64;
+                let mut out = format!("{} in-game day{} is {} ticks, or {} of real time",
+                    amount, if amount == 1.0 { "" } else { "s" }, ticks, real_time(ticks / 20.0));
+                if amount.fract() == 0.0 {
+                    out.push_str(&format!("\nDay {} has a {} moon", amount, MOON_PHASES[(amount as u64 % 8) as usize]));
                 }
+                Ok(out)
+            },
+            _ => Err(CommandError::InvalidSyntax(self.usage().to_string())),
+        }
+    }
+}
+
+pub struct McUuidCommand;
+
+#[async_trait]
+impl Command for McUuidCommand {
+    fn name(&self) -> &'static str {
+        "mcuuid"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <player name|UUID>"
+    }
+    fn about(&self) -> &'static str {
+        "Shows the UUID an offline mode server gives a player, or formats a UUID with dashes and as an NBT int array."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcuuid".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let input = input.trim();
+        if input.is_empty() || input.contains(char::is_whitespace) {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        // Reformat a UUID that was given instead of a name
+        if let Some(uuid) = parse_uuid(input) {
+            return Ok(format!("UUID: `{}`\nNBT: `{}`", dashed(&uuid), int_array(&uuid)));
+        }
+
+        let uuid = offline_uuid(input);
+        let mut out = format!("Offline UUID for {}: `{}`\nNBT: `{}`", input, dashed(&uuid), int_array(&uuid));
+        if !valid_name(input) {
+            out.push_str("\nThis isn't a valid Java Edition name, names have 3 to 16 letters, numbers and underscores");
+        }
+        // Offline UUIDs depend on the exact name
+        if input.chars().any(|c| c.is_ascii_uppercase()) {
+            out.push_str(&format!("\nNames are case sensitive here, {} gets a different UUID", input.to_lowercase()));
+        }
+        Ok(out)
+    }
+}
+
+// How many chunks the slime chunk grid shows in each direction
+const SLIME_RADIUS: i32 = 4;
+
+pub struct McSlimeCommand;
+
+#[async_trait]
+impl Command for McSlimeCommand {
+    fn name(&self) -> &'static str {
+        "mcslime"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <x> <z>"
+    }
+    fn about(&self) -> &'static str {
+        "Shows if the chunk at the given block coordinates is a slime chunk, with a map of the slime chunks around it."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        let config = config.read().unwrap();
+        if !check_permission(&config, "cmd.mcslime".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let seed = match world_seed(&config.seed) {
+            Some(s) => s,
+            None => return Err(CommandError::BadUsage("There is no world seed set up for this bot".to_string())),
+        };
+
+        // Staff can check which seed is set up
+        if input.trim().eq_ignore_ascii_case("seed") {
+            if !check_permission(&config, "admin.seed".to_string(), role) {
+                return Err(CommandError::NoPerms);
+            }
+            return Ok(format!("The world seed is `{}`", config.seed.trim()));
+        }
+
+        let mut coords = Vec::new();
+        for word in input.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty()) {
+            match parse_amount(word, None) {
+                Ok((value, "")) if value.abs() <= 30_000_000.0 => coords.push(value.floor() as i32),
+                _ => return Err(CommandError::BadUsage(format!("`{}` is not a coordinate", word))),
+            }
+        }
+        let (chunk_x, chunk_z) = match coords[..] {
+            [x, z] => (x.div_euclid(16), z.div_euclid(16)),
+            _ => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+        };
+
+        let mut out = vec![format!("Chunk {}, {} {} a slime chunk",
+            chunk_x, chunk_z, if is_slime_chunk(seed, chunk_x, chunk_z) { "is" } else { "is not" })];
+        // North is up, like on the map in game
+        for z in chunk_z - SLIME_RADIUS..=chunk_z + SLIME_RADIUS {
+            let row: String = (chunk_x - SLIME_RADIUS..=chunk_x + SLIME_RADIUS)
+                .map(|x| match (is_slime_chunk(seed, x, z), x == chunk_x && z == chunk_z) {
+                    (true, true) => "🟢",
+                    (false, true) => "🔴",
+                    (true, false) => "🟩",
+                    (false, false) => "⬛",
+                })
+                .collect();
+            out.push(row);
+        }
+        out.push("🟩 slime chunk, ⬛ other chunk, north is up".to_string());
+        Ok(out.join("\n"))
+    }
+}
+
+// Ways to travel, with other names for them and about how many blocks per second they cover
+const TRAVEL_MODES: [(&str, &[&str], f64); 8] = [
+    ("Walking", &["walk", "walking"], 4.317),
+    ("Sprinting", &["sprint", "sprinting", "run"], 5.612),
+    ("Horse", &["horse", "riding"], 9.5),
+    ("Boat", &["boat", "water"], 8.0),
+    ("Boat on ice", &["ice", "iceboat", "packed ice"], 40.0),
+    ("Boat on blue ice", &["blueice", "blue ice", "blue"], 72.7),
+    ("Elytra with rockets", &["elytra", "fly", "flying"], 33.5),
+    ("Minecart", &["minecart", "cart", "rail"], 8.0),
+];
+
+pub struct McTravelCommand;
+
+#[async_trait]
+impl Command for McTravelCommand {
+    fn name(&self) -> &'static str {
+        "mctravel"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <from x> <from z> <to x> <to z> (walk|sprint|horse|boat|ice|blueice|elytra|minecart)"
+    }
+    fn about(&self) -> &'static str {
+        "Works out the distance between two places in Minecraft and how long it takes to get there, also through the Nether."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mctravel".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+
+        let mut coords = Vec::new();
+        let mut words = Vec::new();
+        for word in input.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty()) {
+            match parse_amount(word, None) {
+                Ok((value, "")) if words.is_empty() => coords.push(value),
+                // Everything after the coordinates is the way of travelling
+                _ => words.push(word.to_lowercase()),
+            }
+        }
+        let (from_x, from_z, to_x, to_z) = match coords[..] {
+            [from_x, from_z, to_x, to_z] => (from_x, from_z, to_x, to_z),
+            _ => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+        };
+        if coords.iter().any(|c| c.abs() > 30_000_000.0) {
+            return Err(CommandError::BadUsage("Those coordinates are outside of the world border".to_string()));
+        }
+        let modes: Vec<&(&str, &[&str], f64)> = if words.is_empty() {
+            TRAVEL_MODES.iter().collect()
+        } else {
+            let mode = words.join(" ");
+            match TRAVEL_MODES.iter().find(|(_name, names, _speed)| names.contains(&mode.as_str())) {
+                Some(m) => vec![m],
+                None => return Err(CommandError::BadUsage(format!("I don't know how to travel by {}, try one of walk, sprint, horse, boat, ice, blueice, elytra or minecart", mode))),
+            }
+        };
+
+        let (dx, dz) = ((to_x - from_x).abs(), (to_z - from_z).abs());
+        let straight = (dx * dx + dz * dz).sqrt();
+        // Blocks in the Nether are 8 in the Overworld, when there is a portal near both places
+        let nether = straight / 8.0;
+        let mut out = vec![
+            format!("From {}, {} to {}, {}", from_x, from_z, to_x, to_z),
+            format!("Straight line: {:.0} blocks, following the axes: {:.0} blocks", straight, dx + dz),
+            format!("Through the Nether: {:.0} blocks", nether),
+        ];
+        for (name, _names, speed) in modes {
+            out.push(format!("{}: {} ({} through the Nether)",
+                name, format_duration((straight / speed).round() as i64), format_duration((nether / speed).round() as i64)));
+        }
+        Ok(out.join("\n"))
+    }
+}
+
+// Words before a tool's name that don't change which enchantments it can have
+const MATERIALS: [&str; 12] = ["wooden", "wood", "stone", "iron", "golden", "gold", "diamond", "netherite", "leather", "chainmail", "copper", "turtle"];
+
+// How many enchantments mcanvil works out an order for, as every way of combining them is tried
+const MAX_ENCHANTMENTS: usize = 10;
+
+// Read a level like 3 or III
+fn parse_level(word: &str) -> Option<u32> {
+    match word.to_lowercase().as_str() {
+        "i" => Some(1),
+        "ii" => Some(2),
+        "iii" => Some(3),
+        "iv" => Some(4),
+        "v" => Some(5),
+        w => w.parse().ok(),
+    }
+}
+
+// Format a level the way Minecraft shows it, like Sharpness V or Mending
+fn enchantment_name(enchantment: &Enchantment, level: u32) -> String {
+    const NUMERALS: [&str; 5] = ["I", "II", "III", "IV", "V"];
+    if enchantment.max_level == 1 {
+        return enchantment.name.to_string();
+    }
+    match NUMERALS.get(level as usize - 1) {
+        Some(n) => format!("{} {}", enchantment.name, n),
+        None => format!("{} {}", enchantment.name, level),
+    }
+}
+
+// Find the longest run of words at the start that names something, returning it and how many words it used.
+// Exact names are looked for first, so a typo allowed in a long run doesn't swallow the next name.
+fn longest_name<'a, T: Copy>(words: &[String], options: impl Fn() -> Vec<(&'a str, T)>) -> Option<(T, usize)> {
+    // Levels are never part of a name
+    let words = &words[..words.iter().position(|w| parse_level(w).is_some()).unwrap_or(words.len())];
+    let lengths = || (1..=words.len().min(4)).rev();
+    lengths()
+        .find_map(|length| {
+            let name = normalise(&words[..length].join(" "));
+            options().into_iter().find(|(n, _t)| normalise(n) == name).map(|(_n, t)| (t, length))
+        })
+        .or_else(|| lengths().find_map(|length| closest(&words[..length].join(" "), options().into_iter()).map(|t| (t, length))))
+}
 
-                let items: f32 = s * 1728.0;
+pub struct McAnvilCommand;
 
-                return Ok(format!(
-                    "{} shulker{} break{} down into {:.0} item{}", 
-                    shulkers,
-                    if s == 1.0 {""} else {"s"},
-                    if s != 1.0 {""} else {"s"},
-                    items,
-                    if items == 1.0 { "" } else { "s" }));
+#[async_trait]
+impl Command for McAnvilCommand {
+    fn name(&self) -> &'static str {
+        "mcanvil"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <item> <enchantment> (level) <enchantment> (level)..."
+    }
+    fn about(&self) -> &'static str {
+        "Works out the cheapest order to combine enchanted books onto an item in an anvil, without any step being Too Expensive."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcanvil".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let table = enchantments();
+        let words: Vec<String> = input.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| !w.is_empty() && *w != "and")
+            .map(|w| w.to_lowercase())
+            .skip_while(|w| MATERIALS.contains(&w.as_str()))
+            .collect();
+        if words.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+
+        let tool_names = || table.tools.iter()
+            .flat_map(|t| std::iter::once(t.name.as_str()).chain(t.aliases.iter().map(|a| a.as_str())).map(move |n| (n, t)))
+            .collect();
+        let (tool, length) = match longest_name(&words, tool_names) {
+            Some(t) => t,
+            None => return Err(CommandError::BadUsage(format!("I don't know the item {}, try something like sword, pickaxe or boots", words[0]))),
+        };
+        let enchantment_names = || table.enchantments.iter()
+            .flat_map(|e| std::iter::once(e.name.as_str()).chain(e.aliases.iter().map(|a| a.as_str())).map(move |n| (n, e)))
+            .collect();
+
+        let mut wanted: Vec<(&Enchantment, u32)> = Vec::new();
+        let mut i = length;
+        while i < words.len() {
+            let (enchantment, length) = match longest_name(&words[i..], enchantment_names) {
+                Some(e) => e,
+                None => return Err(CommandError::BadUsage(format!("I don't know the enchantment {}", words[i]))),
+            };
+            i += length;
+            // Without a level, the highest one is meant
+            let level = match words.get(i).and_then(|w| parse_level(w)) {
+                Some(level) => {
+                    i += 1;
+                    level
+                },
+                None => enchantment.max_level,
+            };
+            if level == 0 || level > enchantment.max_level {
+                return Err(CommandError::BadUsage(format!("{} only goes up to level {}", enchantment.name, enchantment.max_level)));
+            }
+            if !enchantment.tools.contains(&tool.name) {
+                return Err(CommandError::BadUsage(format!("{} can't be put on a {}", enchantment.name, tool.name.to_lowercase())));
             }
+            if let Some((other, _level)) = wanted.iter().find(|(e, _level)| e.name == enchantment.name || table.exclusive(&e.name, &enchantment.name)) {
+                return Err(if other.name == enchantment.name {
+                    CommandError::BadUsage(format!("{} is in the list twice", enchantment.name))
+                } else {
+                    CommandError::BadUsage(format!("{} and {} can't be on the same item", other.name, enchantment.name))
+                });
+            }
+            wanted.push((enchantment, level));
+        }
+        if wanted.is_empty() {
+            return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        }
+        if wanted.len() > MAX_ENCHANTMENTS {
+            return Err(CommandError::BadUsage(format!("I can only work out an order for up to {} enchantments", MAX_ENCHANTMENTS)));
+        }
+
+        let values: Vec<u32> = wanted.iter().map(|(e, level)| e.multiplier * level).collect();
+        let (steps, total, work) = match cheapest_order(&values) {
+            Some(order) => order,
+            None => return Ok(format!("There is no order that doesn't have a step costing {} levels or more, which is Too Expensive!", TOO_EXPENSIVE)),
+        };
+
+        let names = |mask: u32| wanted.iter().enumerate()
+            .filter(|(i, _e)| mask & (1 << i) != 0)
+            .map(|(_i, (e, level))| enchantment_name(e, *level))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let mut out = vec![format!("Cheapest order for {} with {}:", tool.name, names((1 << wanted.len()) - 1))];
+        for (n, step) in steps.iter().enumerate() {
+            let target = match step.target {
+                Piece::Item(0) => tool.name.to_string(),
+                Piece::Item(mask) => format!("{} ({})", tool.name, names(mask)),
+                Piece::Book(mask) => format!("Book ({})", names(mask)),
+            };
+            out.push(format!("{}. {} + Book ({}): {} level{}", n + 1, target, names(step.sacrifice), step.cost, pluralize(step.cost as u64)));
+        }
+        out.push(format!("Total: {} level{}. The item has {} prior work, so using the anvil on it again costs {} more level{}.",
+            total, pluralize(total as u64), work, prior_work_penalty(work), pluralize(prior_work_penalty(work) as u64)));
+        Ok(out.join("\n"))
+    }
+}
+
+// An amount of an item with how many stacks it makes, like "200 Cobblestone (3 stacks + 8)"
+fn in_stacks(amount: u64, id: &str) -> String {
+    let name = display_name(id);
+    // Tags are mostly blocks, which stack to 64
+    let stack = if id.starts_with('#') { 64 } else { find_item(&name).map_or(64, |i| i.stack as u64) };
+    let (stacks, left) = (amount / stack, amount % stack);
+    match (stack, stacks, left) {
+        (1, _, _) | (_, 0, _) => format!("{} {}", amount, name),
+        (_, _, 0) => format!("{} {} ({} stack{})", amount, name, stacks, pluralize(stacks)),
+        _ => format!("{} {} ({} stack{} + {})", amount, name, stacks, pluralize(stacks), left),
+    }
+}
+
+pub struct McCraftCommand;
+
+#[async_trait]
+impl Command for McCraftCommand {
+    fn name(&self) -> &'static str {
+        "mccraft"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <amount> <item>"
+    }
+    fn about(&self) -> &'static str {
+        "Works out the raw materials needed to craft an amount of a Minecraft item, following the recipes all the way down."
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mccraft".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+        let (amount, item) = count_of_item(&input, self.usage())?;
+        let item = match item {
+            Some(i) => i,
+            None => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+        };
+        if amount == 0 {
+            return Err(CommandError::BadUsage("The amount needs to be at least 1".to_string()));
+        }
+
+        let dir = config.read().unwrap().recipes.to_string();
+        let recipes = recipes(&dir);
+        let names: Vec<(String, &str)> = recipes.keys()
+            .flat_map(|id| [(display_name(id), id.as_str()), (id.rsplit(':').next().unwrap_or_default().to_string(), id.as_str())])
+            .collect();
+        let id = match closest(&item.name, names.iter().map(|(name, id)| (name.as_str(), *id))) {
+            Some(id) => id,
+            None => return Err(CommandError::BadUsage(format!("I don't know a crafting recipe for {}", item.name))),
+        };
+
+        let materials = materials(&recipes, id, amount);
+        let list = |items: &[(String, u64)]| items.iter().map(|(id, amount)| in_stacks(*amount, id)).collect::<Vec<String>>();
+        let mut out = vec![format!("Crafting {} needs:", in_stacks(amount, id))];
+        out.extend(list(&materials.raw).iter().map(|i| format!("- {}", i)));
+        if !materials.crafted.is_empty() {
+            out.push(format!("Crafted on the way: {}", list(&materials.crafted).join(", ")));
+        }
+        if !materials.left_over.is_empty() {
+            out.push(format!("Left over: {}", list(&materials.left_over).join(", ")));
         }
-        return Err(CommandError::InvalidSyntax(self.usage().to_string()));
+        Ok(out.join("\n"))
     }
 }
 
 
-fn pluralize(input: i32) -> &'static str {
+fn pluralize(input: u64) -> &'static str {
     return if input == 1 { "" } else { "s" };
 }
 
-fn third_person_ending(input: i32) -> &'static str {
+fn third_person_ending(input: u64) -> &'static str {
     return if input != 1 { "" } else { "s" };
 }
@@ -1,9 +1,20 @@
 use std::sync::{Arc, RwLock};
 use std::fmt;
 
+use serenity::async_trait;
+use serenity::client::Context;
+use serenity::model::channel::Message;
+
 pub mod admin;
+pub mod applications;
+pub mod colour;
+pub mod coords;
+pub mod format;
+pub mod link;
 pub mod minecraft;
+pub mod rcon;
 pub mod regex;
+pub mod status;
 pub mod temperature;
 pub mod timezone;
 
@@ -30,9 +41,15 @@ impl fmt::Display for CommandError {
     }
 }
 
+#[async_trait]
 pub trait Command: Sync + Send  {
     fn name(&self) -> &'static str;
     fn usage(&self) -> &'static str;
     fn about(&self) -> &'static str;
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError>;
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError>;
+    // Run the command for a message on Discord, for commands that need more than the text, like replying with a file.
+    // Commands that send their own reply return an empty string.
+    async fn execute_message(&self, _ctx: &Context, _msg: &Message, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        self.execute(config, role, input).await
+    }
 }
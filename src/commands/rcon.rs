@@ -0,0 +1,87 @@
+use std::sync::{Arc, RwLock};
+
+use serenity::async_trait;
+
+use crate::config::{Config, Role};
+use crate::commands::{Command, CommandError};
+use crate::util::format::strip_codes;
+use crate::util::perms::check_permission;
+use crate::util::rcon::run_command;
+use crate::util::uuid::valid_name;
+
+pub struct McCommand;
+
+impl McCommand {
+    // Check if the role may run a raw command, either by its name like mc.cmd.time or all of them with mc.cmd.*
+    fn can_run(config: &Config, role: &Role, command: &str) -> bool {
+        let perm = format!("mc.cmd.{}", command);
+        if check_permission(config, perm.to_string(), role) {
+            return true;
+        }
+        // A specific denial still wins over the wildcard
+        !role.perms.contains(&format!("-{}", perm)) && check_permission(config, "mc.cmd.*".to_string(), role)
+    }
+}
+
+#[async_trait]
+impl Command for McCommand {
+    fn name(&self) -> &'static str {
+        "mc"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: <list|whitelist <add|remove|list> (player)|say <message>|cmd <command>>"
+    }
+    fn about(&self) -> &'static str {
+        "Runs commands on the Minecraft server over RCON"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        let (subcommand, args) = input.trim().split_once(' ').unwrap_or((input.trim(), ""));
+        let args = args.trim();
+
+        // Find the command to send and the permission needed for it
+        let (perm, command) = match subcommand.to_lowercase().as_str() {
+            "list" => ("mc.list", "list".to_string()),
+            "whitelist" => {
+                let (action, name) = args.split_once(' ').unwrap_or((args, ""));
+                match (action.to_lowercase().as_str(), name.trim()) {
+                    ("list", _) => ("mc.whitelist", "whitelist list".to_string()),
+                    // A valid name can't be used to sneak extra arguments into the command
+                    ("add", name) | ("remove", name) if valid_name(name) =>
+                        ("mc.whitelist", format!("whitelist {} {}", action.to_lowercase(), name)),
+                    _ => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+                }
+            },
+            "say" if !args.is_empty() => ("mc.say", format!("say {}", args.replace('\n', " "))),
+            "cmd" if !args.is_empty() => {
+                let name = args.trim_start_matches('/').split(' ').next().unwrap_or_default().to_lowercase();
+                if !Self::can_run(&config.read().unwrap(), role, &name) {
+                    return Err(CommandError::NoPerms);
+                }
+                ("mc.cmd", args.trim_start_matches('/').replace('\n', " "))
+            },
+            _ => return Err(CommandError::InvalidSyntax(self.usage().to_string())),
+        };
+
+        if !check_permission(&config.read().unwrap(), perm.to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+
+        let rcon = config.read().unwrap().rcon.clone();
+        if rcon.password.is_empty() {
+            return Err(CommandError::BadUsage("RCON is not set up for this bot".to_string()));
+        }
+
+        match run_command(&rcon, &command).await {
+            Ok(output) => {
+                // Remove colour codes and keep the reply within Discord's limits
+                let output = strip_codes(&output).trim().to_string();
+                if output.is_empty() {
+                    Ok("Done!".to_string())
+                } else {
+                    Ok(format!("```\n{}\n```", output.chars().take(1900).collect::<String>()))
+                }
+            },
+            Err(e) => Err(CommandError::BadUsage(e.to_string())),
+        }
+    }
+}
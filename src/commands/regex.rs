@@ -1,5 +1,7 @@
 use std::sync::{Arc, RwLock};
 
+use serenity::async_trait;
+
 use crate::config::{Config, Role};
 use crate::commands::{Command, CommandError};
 use crate::util::perms::check_permission;
@@ -7,6 +9,7 @@ use crate::util::regexresponse::regexresponse;
 
 pub struct RegexCommand;
 
+#[async_trait]
 impl Command for RegexCommand {
     fn name(&self) -> &'static str {
         "regex"
@@ -17,7 +20,7 @@ impl Command for RegexCommand {
     fn about(&self) -> &'static str {
         "Forces a check for a regex match, even if the user bypasses regex"
     }
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
         if check_permission(&config.read().unwrap(), "cmd.regex".to_string(), role) {
             if let Some(r) = regexresponse(config, input.to_string()) {
                     return Ok(r.0);
@@ -0,0 +1,127 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use futures::future::join_all;
+use serenity::async_trait;
+
+use crate::config::{Config, Role};
+use crate::commands::{Command, CommandError};
+use crate::storage::{Storage, Uptime};
+use crate::util::duration::format_duration;
+use crate::util::perms::check_permission;
+use crate::util::ping::{ping, ServerStatus};
+
+// Describe a server's status over a few lines
+fn describe(name: &str, status: &ServerStatus) -> String {
+    let mut out = format!("**{}** is online ({}ms)\nVersion: {}\nPlayers: {}/{}",
+        name, status.latency, status.version, status.online, status.max);
+    if !status.sample.is_empty() {
+        out.push_str(&format!(" ({})", status.sample.join(", ")));
+    }
+    for line in status.motd.lines().filter(|l| !l.trim().is_empty()) {
+        out.push_str(&format!("\n> {}", line.trim()));
+    }
+    out
+}
+
+pub struct McStatusCommand;
+
+#[async_trait]
+impl Command for McStatusCommand {
+    fn name(&self) -> &'static str {
+        "mcstatus"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: (server)"
+    }
+    fn about(&self) -> &'static str {
+        "Shows if the Minecraft server is online, with its version, players and MOTD"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.mcstatus".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+
+        let servers = config.read().unwrap().servers.clone();
+        if servers.is_empty() {
+            return Err(CommandError::BadUsage("There are no servers set up for this bot".to_string()));
+        }
+
+        let name = input.trim().to_lowercase();
+        if name.is_empty() && servers.len() > 1 {
+            // Give a short overview of all servers, pinged at the same time so offline ones don't add up their timeouts
+            let pings = join_all(servers.values().map(|s| ping(&s.address, s.timeout))).await;
+            let out: Vec<String> = servers.keys().zip(pings).map(|(name, result)| match result {
+                Ok(s) => format!("🟢 **{}** {}/{} players", name, s.online, s.max),
+                Err(_) => format!("🔴 **{}** offline", name),
+            }).collect();
+            return Ok(out.join("\n"));
+        }
+
+        let (name, server) = match servers.iter().find(|(n, _s)| name.is_empty() || n.to_lowercase() == name) {
+            Some(s) => s,
+            None => return Err(CommandError::BadUsage(format!("No server called {}, try one of {}",
+                name, servers.keys().cloned().collect::<Vec<String>>().join(", ")))),
+        };
+        match ping(&server.address, server.timeout).await {
+            Ok(status) => Ok(describe(name, &status)),
+            Err(e) => Ok(format!("**{}** is offline ({})", name, e)),
+        }
+    }
+}
+
+pub struct UptimeCommand {
+    pub storage: Arc<RwLock<Storage>>,
+}
+
+impl UptimeCommand {
+    // Describe the uptime of a server over the last day, week, and since the monitor started checking it
+    fn describe(name: &str, uptime: &Uptime, now: i64) -> String {
+        let percentage = |period: i64| {
+            let start = (now - period).max(uptime.first_check);
+            if now <= start {
+                return "100%".to_string();
+            }
+            let up = 1.0 - uptime.downtime_since(start, now) as f64 / (now - start) as f64;
+            format!("{:.2}%", (up * 100.0).max(0.0))
+        };
+        let mut out = format!("**{}** uptime: {} last 24 hours, {} last 7 days, {} since <t:{}:d>",
+            name, percentage(86400), percentage(604800), percentage(now - uptime.first_check), uptime.first_check);
+        if let Some(since) = uptime.down_since {
+            out.push_str(&format!("\nDown since <t:{}:R>", since));
+        } else if let Some(outage) = uptime.outages.last() {
+            out.push_str(&format!("\nLast outage <t:{}:R>, lasting {}", outage.start, format_duration(outage.end - outage.start)));
+        }
+        out
+    }
+}
+
+#[async_trait]
+impl Command for UptimeCommand {
+    fn name(&self) -> &'static str {
+        "uptime"
+    }
+    fn usage(&self) -> &'static str {
+        "Usage: (server)"
+    }
+    fn about(&self) -> &'static str {
+        "Shows how much of the time the Minecraft servers were online, as recorded by the status monitor"
+    }
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+        if !check_permission(&config.read().unwrap(), "cmd.uptime".to_string(), role) {
+            return Err(CommandError::NoPerms);
+        }
+
+        let name = input.trim().to_lowercase();
+        let now = Utc::now().timestamp();
+        let storage = self.storage.read().unwrap();
+        let out: Vec<String> = storage.uptime.iter()
+            .filter(|(n, u)| u.checks > 0 && (name.is_empty() || n.to_lowercase() == name))
+            .map(|(n, u)| Self::describe(n, u, now))
+            .collect();
+        if out.is_empty() {
+            return Err(CommandError::BadUsage("No uptime has been recorded for that server yet".to_string()));
+        }
+        Ok(out.join("\n"))
+    }
+}
@@ -3,6 +3,7 @@ use std::str::FromStr;
 
 use chrono::{DateTime, NaiveTime, Utc};
 use chrono_tz::Tz;
+use serenity::async_trait;
 
 use crate::config::{Config, Role};
 use crate::commands::{Command, CommandError};
@@ -30,6 +31,7 @@ fn capitalise_timezone(input_str: String) -> String {
 
 pub struct TimezoneCommand;
 
+#[async_trait]
 impl Command for TimezoneCommand {
     fn name(&self) -> &'static str {
         "timezone"
@@ -41,7 +43,7 @@ impl Command for TimezoneCommand {
         "Converts a given time and timezone to the unix timestamp, which can be embedded with Discord to the user's local time.
         Can optionally specify a second timezone to do a direct conversion. This command also links to https://time.is for more information"
     }
-    fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
+    async fn execute(&self, config: Arc<RwLock<Config>>, role: &Role, input: String) -> Result<String, CommandError> {
         if check_permission(&config.read().unwrap(), "cmd.timezone".to_string(), role) {
             // Get all characters matching 0-9 and :
             let mut time_str = input.split(' ').take(1).next().unwrap_or_default().to_uppercase()
@@ -8,9 +8,14 @@ use std::collections::BTreeMap;
 #[serde(default)]
 pub struct Role {
     pub id: u64,
+    pub users: Vec<u64>,
+    pub discord_permissions: Vec<String>,
+    // Users who match several roles by their Discord roles or permissions get the one with the highest priority
+    pub priority: i64,
     pub webhook_regex: String,
     pub inherit: String,
     pub perms: Vec<String>,
+    pub channels: BTreeMap<String, Vec<String>>,
 }
 
 impl Default for Role {
@@ -18,6 +23,9 @@ impl Default for Role {
     fn default() -> Self {
         Self {
             id: 0,
+            users: Vec::new(),
+            discord_permissions: Vec::new(),
+            priority: 0,
             webhook_regex: "".to_string(),
             inherit: "".to_string(),
             perms: Vec::from([
@@ -26,9 +34,223 @@ impl Default for Role {
                 "cmd.mcitems".to_string(),
                 "cmd.mcshulkers".to_string(),
                 "cmd.mcunshulker".to_string(),
+                "cmd.mccontainers".to_string(),
+                "cmd.mccoords".to_string(),
+                "cmd.mcxp".to_string(),
+                "cmd.mctime".to_string(),
+                "cmd.mcuuid".to_string(),
+                "cmd.mcslime".to_string(),
+                "cmd.mcformat".to_string(),
+                "cmd.mcjson".to_string(),
+                "cmd.mcsnbt".to_string(),
+                "cmd.mctravel".to_string(),
+                "cmd.mcanvil".to_string(),
+                "cmd.mccraft".to_string(),
+                "cmd.mccolor".to_string(),
+                "cmd.link".to_string(),
+                "cmd.whois".to_string(),
+                "cmd.apply".to_string(),
+                "cmd.mcstatus".to_string(),
+                "cmd.uptime".to_string(),
                 "cmd.temperature".to_string(),
                 "cmd.timezone".to_string(),
+                "logs.analyze".to_string(),
                 ]),
+            channels: BTreeMap::new(),
+        }
+    }
+}
+
+// A bot or webhook that posts messages from Minecraft, with regexes to find the player in its messages
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Bridge {
+    pub source: u64,
+    pub patterns: BTreeMap<String, String>,
+}
+
+// Connection details for the Minecraft server's RCON
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RconConfig {
+    pub address: String,
+    pub password: String,
+    pub timeout: u64,
+}
+
+impl Default for RconConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:25575".to_string(),
+            password: "".to_string(),
+            timeout: 5,
+        }
+    }
+}
+
+// A Minecraft server to check the status of
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub address: String,
+    pub timeout: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:25565".to_string(),
+            timeout: 5,
+        }
+    }
+}
+
+// Settings for the task that keeps checking the servers
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MonitorConfig {
+    pub enabled: bool,
+    pub channel: u64,
+    pub alert_channel: u64,
+    pub interval: u64,
+    pub failures: u32,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channel: 0,
+            alert_channel: 0,
+            interval: 60,
+            failures: 3,
+        }
+    }
+}
+
+// A web map of the Minecraft server, like Dynmap, BlueMap or squaremap, to link coordinates to
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MapConfig {
+    // Which kind of map this is, as they all use different links
+    pub kind: String,
+    pub url: String,
+    pub zoom: i32,
+    // The names the map uses for the overworld, nether and end
+    pub worlds: BTreeMap<String, String>,
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        Self {
+            kind: "dynmap".to_string(),
+            url: "".to_string(),
+            zoom: 4,
+            worlds: BTreeMap::from([
+                ("overworld".to_string(), "world".to_string()),
+                ("nether".to_string(), "world_nether".to_string()),
+                ("end".to_string(), "world_the_end".to_string()),
+            ]),
+        }
+    }
+}
+
+// Settings for finding known problems in logs and crash reports that are uploaded or pasted
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LogConfig {
+    pub rules: String,
+    // The largest file to download, in bytes
+    pub max_size: u64,
+    pub max_matches: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            rules: "./log_rules.toml".to_string(),
+            max_size: 8_000_000,
+            max_matches: 5,
+        }
+    }
+}
+
+// Settings for linking Minecraft players to Discord accounts with !link
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LinkConfig {
+    // The command sent over RCON to give a player their code, with %player% and %code% filled in
+    pub command: String,
+    // How many seconds a code can be used for
+    pub expiry: i64,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            command: "tell %player% Your code to link your Discord account is %code%, send !link %code% on Discord to finish".to_string(),
+            expiry: 600,
+        }
+    }
+}
+
+// Settings for whitelist applications made with !apply and reviewed by staff
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ApplicationConfig {
+    // The channel applications are posted in for review, applying is turned off while this is 0
+    pub channel: u64,
+    // Where the questions are asked, "dm" for DMs or "thread" for a private thread in the channel !apply was used in
+    pub form: String,
+    pub questions: Vec<String>,
+    // The command sent over RCON when an application is approved, with %player% filled in
+    pub command: String,
+    // The Discord role given to approved applicants, or 0 for none
+    pub role: u64,
+}
+
+impl Default for ApplicationConfig {
+    fn default() -> Self {
+        Self {
+            channel: 0,
+            form: "dm".to_string(),
+            questions: Vec::from([
+                "How old are you?".to_string(),
+                "How did you find the server?".to_string(),
+                "Why do you want to join?".to_string(),
+            ]),
+            command: "whitelist add %player%".to_string(),
+            role: 0,
+        }
+    }
+}
+
+// Something that holds items, with how many slots it has and what goes in them
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Container {
+    pub slots: u64,
+    // The name of another container that fills each slot, like shulkers in a chest, or empty for stacks of items
+    pub contains: String,
+    pub aliases: Vec<String>,
+}
+
+impl Default for Container {
+    fn default() -> Self {
+        Self {
+            slots: 27,
+            contains: "".to_string(),
+            aliases: Vec::new(),
+        }
+    }
+}
+
+impl Container {
+    fn new(slots: u64, contains: &str, aliases: &[&str]) -> Self {
+        Self {
+            slots,
+            contains: contains.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
         }
     }
 }
@@ -40,13 +262,30 @@ pub struct Config {
     pub bot_token: String,
     pub prefix: String,
     pub trim_regex: String,
+    pub webhook_extractor: String,
     pub command_cooldown: u64,
+    pub per_player_cooldowns: bool,
     pub regex_response_cooldown: u64,
     pub enabled_utils: Vec<String>,
+    // The world seed, which is only shown to those with the admin.seed permission
+    pub seed: String,
+    // A directory of recipe files in the data pack format, used by mccraft along with the bundled recipes
+    pub recipes: String,
     pub responses: BTreeMap<String, String>,
     pub regex_responses: BTreeMap<String, String>,
     pub aliases: BTreeMap<String, Vec<String>>,
     pub roles: BTreeMap<String, Role>,
+    pub users: BTreeMap<String, Vec<String>>,
+    pub linked_accounts: BTreeMap<String, u64>,
+    pub bridges: BTreeMap<String, Bridge>,
+    pub rcon: RconConfig,
+    pub servers: BTreeMap<String, ServerConfig>,
+    pub monitor: MonitorConfig,
+    pub containers: BTreeMap<String, Container>,
+    pub map: MapConfig,
+    pub logs: LogConfig,
+    pub linking: LinkConfig,
+    pub applications: ApplicationConfig,
 }
 
 impl Default for Config {
@@ -54,19 +293,29 @@ impl Default for Config {
     fn default() -> Self {
         let admin = Role {
             id: 123456781234567812,
+            users: Vec::new(),
+            discord_permissions: Vec::from(["Administrator".to_string()]),
+            priority: 100,
             webhook_regex: "\\[Admin\\].*".to_string(),
             inherit: "default".to_string(),
             perms: Vec::from([
                 "admin.reload".to_string(),
+                "admin.grant".to_string(),
+                "admin.seed".to_string(),
+                "cmd.applications".to_string(),
+                "applications.review".to_string(),
                 "bypass.regex".to_string(),
                 "bypass.cooldown".to_string(),
             ]),
+            channels: BTreeMap::new(),
         };
         Self {
             bot_token: "XXXXXX".to_string(),
             prefix: "!".to_string(),
             trim_regex: "".to_string(),
+            webhook_extractor: "".to_string(),
             command_cooldown: 15,
+            per_player_cooldowns: false,
             regex_response_cooldown: 45,
             enabled_utils: Vec::from([
                 "regex".to_string(),
@@ -74,9 +323,30 @@ impl Default for Config {
                 "mcitems".to_string(),
                 "mcshulkers".to_string(),
                 "mcunshulker".to_string(),
+                "mccontainers".to_string(),
+                "mccoords".to_string(),
+                "mcxp".to_string(),
+                "mctime".to_string(),
+                "mcuuid".to_string(),
+                "mcslime".to_string(),
+                "mcformat".to_string(),
+                "mcjson".to_string(),
+                "mcsnbt".to_string(),
+                "mctravel".to_string(),
+                "mcanvil".to_string(),
+                "mccraft".to_string(),
+                "mccolor".to_string(),
+                "link".to_string(),
+                "whois".to_string(),
+                "apply".to_string(),
+                "applications".to_string(),
+                "mcstatus".to_string(),
+                "uptime".to_string(),
                 "temperature".to_string(),
                 "timezone".to_string(),
             ]),
+            seed: "".to_string(),
+            recipes: "./recipes".to_string(),
             responses: BTreeMap::from([
                 ("ping".to_string(), "Pong!".to_string()),
                 ("pong".to_string(), "Ping!".to_string()),
@@ -90,7 +360,29 @@ impl Default for Config {
             roles: BTreeMap::from([
                 ("default".to_string(), Role::default()),
                 ("admin".to_string(), admin),
-            ])
+            ]),
+            users: BTreeMap::new(),
+            linked_accounts: BTreeMap::new(),
+            bridges: BTreeMap::new(),
+            rcon: RconConfig::default(),
+            servers: BTreeMap::from([
+                ("main".to_string(), ServerConfig::default()),
+            ]),
+            monitor: MonitorConfig::default(),
+            containers: BTreeMap::from([
+                ("chest".to_string(), Container::new(27, "", &["trapped chest", "chest minecart", "minecart with chest"])),
+                ("double_chest".to_string(), Container::new(54, "", &["large chest", "dc"])),
+                ("barrel".to_string(), Container::new(27, "", &[])),
+                ("shulker".to_string(), Container::new(27, "", &["shulker box", "shulkerbox"])),
+                ("shulker_double_chest".to_string(), Container::new(54, "shulker", &["double chest of shulkers", "shulker chest"])),
+                ("hopper".to_string(), Container::new(5, "", &["hopper minecart", "minecart with hopper"])),
+                ("dispenser".to_string(), Container::new(9, "", &["dropper"])),
+                ("bundle".to_string(), Container::new(1, "", &[])),
+            ]),
+            map: MapConfig::default(),
+            logs: LogConfig::default(),
+            linking: LinkConfig::default(),
+            applications: ApplicationConfig::default(),
         }
     }
 }
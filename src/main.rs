@@ -1,31 +1,53 @@
 use serenity::async_trait;
 use serenity::prelude::GatewayIntents;
 use serenity::client::{Client, Context, EventHandler};
-use serenity::model::channel::Message;
+use serenity::model::application::interaction::{Interaction, InteractionResponseType};
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::channel::{Channel, Message};
+use serenity::model::gateway::Ready;
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
 use serenity::framework::standard::{
     StandardFramework,
     macros::group,
 };
 use regex::Regex;
 use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::time::Instant;
 
 mod util;
+use util::applications::{answer, review, APPROVE, DENY};
+use util::bridge::bridged_player;
+use util::links::{linked_player, linked_user};
+use util::logs::{analyze, code_blocks, rules};
 use util::response::response;
 use util::regexresponse::regexresponse;
-use util::perms::check_permission;
+use util::perms::{check_permission, has_discord_permission, user_role, Scope};
 
 mod config;
-use config::Config;
+use config::{Config, Role};
+
+mod storage;
+use storage::Storage;
+
+mod monitor;
 
 mod commands;
 use commands::{Command, CommandError};
-use commands::minecraft::{McItemsCommand, McStacksCommand, McShulkersCommand, McUnshulkerCommand};
+use commands::applications::{ApplicationsCommand, ApplyCommand};
+use commands::colour::McColorCommand;
+use commands::coords::McCoordsCommand;
+use commands::format::{McFormatCommand, McJsonCommand, McSnbtCommand};
+use commands::link::{LinkCommand, WhoisCommand};
+use commands::minecraft::{McAnvilCommand, McContainersCommand, McCraftCommand, McItemsCommand, McStacksCommand, McShulkersCommand, McSlimeCommand, McTimeCommand, McTravelCommand, McUnshulkerCommand, McUuidCommand, McXpCommand};
 use commands::temperature::TemperatureCommand;
 use commands::timezone::TimezoneCommand;
-use commands::admin::ReloadCommand;
+use commands::admin::{GrantCommand, ReloadCommand, RevokeCommand};
+use commands::rcon::McCommand;
 use commands::regex::RegexCommand;
+use commands::status::{McStatusCommand, UptimeCommand};
 
 #[group]
 struct General;
@@ -34,73 +56,269 @@ struct General;
 struct Handler {
     // The config for the bot
     config: Arc<RwLock<Config>>,
+    // Data kept between restarts, like temporary grants
+    storage: Arc<RwLock<Storage>>,
     // HashMap of commands by name and the function
-    registered_commands: Arc<RwLock<HashMap<String, Box<dyn Command>>>>,
+    registered_commands: Arc<RwLock<HashMap<String, Arc<dyn Command>>>>,
     // HashMap of command cooldowns
     command_cooldowns: Arc<RwLock<HashMap<String, Instant>>>,
     // HashMap of Regex cooldowns
     regex_cooldowns: Arc<RwLock<HashMap<String, Instant>>>,
+    // Whether the server status monitor is running, as ready can fire again after reconnecting
+    monitor_started: AtomicBool,
 }
 
 impl Handler {
     // Add command
     pub fn register_command(&mut self, command: Box<dyn Command>) {
         let name = &command.name();
-        self.registered_commands.write().unwrap().insert(name.to_string(), command);
+        self.registered_commands.write().unwrap().insert(name.to_string(), Arc::from(command));
     }
     // Create handler with data
     pub fn new() -> Handler {
         let mut handler = Handler {
             config: Arc::new(RwLock::new(Config::get())),
+            storage: Arc::new(RwLock::new(Storage::get())),
             registered_commands: Arc::new(RwLock::new(HashMap::new())),
             command_cooldowns: Arc::new(RwLock::new(HashMap::new())),
-            regex_cooldowns: Arc::new(RwLock::new(HashMap::new()))
+            regex_cooldowns: Arc::new(RwLock::new(HashMap::new())),
+            monitor_started: AtomicBool::new(false),
         };
+        let storage = handler.storage.clone();
         // Register commands
         Self::register_command(&mut handler, Box::new(McItemsCommand));
         Self::register_command(&mut handler, Box::new(McStacksCommand));
         Self::register_command(&mut handler, Box::new(McShulkersCommand));
         Self::register_command(&mut handler, Box::new(McUnshulkerCommand));
+        Self::register_command(&mut handler, Box::new(McContainersCommand));
+        Self::register_command(&mut handler, Box::new(McCoordsCommand));
+        Self::register_command(&mut handler, Box::new(McXpCommand));
+        Self::register_command(&mut handler, Box::new(McTimeCommand));
+        Self::register_command(&mut handler, Box::new(McTravelCommand));
+        Self::register_command(&mut handler, Box::new(McAnvilCommand));
+        Self::register_command(&mut handler, Box::new(McCraftCommand));
+        Self::register_command(&mut handler, Box::new(McColorCommand));
+        Self::register_command(&mut handler, Box::new(McUuidCommand));
+        Self::register_command(&mut handler, Box::new(McSlimeCommand));
+        Self::register_command(&mut handler, Box::new(McFormatCommand));
+        Self::register_command(&mut handler, Box::new(McJsonCommand));
+        Self::register_command(&mut handler, Box::new(McSnbtCommand));
+        Self::register_command(&mut handler, Box::new(McStatusCommand));
         Self::register_command(&mut handler, Box::new(TemperatureCommand));
         Self::register_command(&mut handler, Box::new(TimezoneCommand));
         Self::register_command(&mut handler, Box::new(ReloadCommand));
+        Self::register_command(&mut handler, Box::new(GrantCommand { storage: storage.clone() }));
+        Self::register_command(&mut handler, Box::new(RevokeCommand { storage: storage.clone() }));
+        Self::register_command(&mut handler, Box::new(LinkCommand { storage: storage.clone() }));
+        Self::register_command(&mut handler, Box::new(WhoisCommand { storage: storage.clone() }));
+        Self::register_command(&mut handler, Box::new(ApplyCommand { storage: storage.clone() }));
+        Self::register_command(&mut handler, Box::new(ApplicationsCommand { storage: storage.clone() }));
+        Self::register_command(&mut handler, Box::new(UptimeCommand { storage }));
         Self::register_command(&mut handler, Box::new(RegexCommand));
+        Self::register_command(&mut handler, Box::new(McCommand));
 
         // Return handler
         handler
     }
 
+    // Get the name of the role for a Discord user from their ID, Discord roles and permissions
+    async fn discord_role(&self, ctx: &Context, guild: Option<GuildId>, user: UserId, roles: &[RoleId]) -> String {
+        // Roles given to the user by their ID come first
+        if let Some((name, _role)) = self.config.read().unwrap().roles.iter().find(|(_name, role)|
+        role.users.contains(user.as_u64())) {
+            return name.to_string();
+        }
+        // Members can have several Discord roles or permissions that match a role, so the one with the highest priority is used,
+        // and the first by name if they are the same
+        let highest = |matches: &dyn Fn(&Role) -> bool| self.config.read().unwrap().roles.iter()
+            .filter(|(_name, role)| matches(role))
+            .max_by_key(|(name, role)| (role.priority, Reverse(*name)))
+            .map(|(name, _role)| name.to_string());
+        if let Some(name) = highest(&|role| roles.iter().any(|r| role.id == *r.as_u64())) {
+            return name;
+        }
+        // Then roles given by the user's Discord permissions, so staff keep their role if Discord roles change
+        let uses_permissions = self.config.read().unwrap().roles.values().any(|role| !role.discord_permissions.is_empty());
+        if uses_permissions {
+            if let Some(guild) = guild.and_then(|g| g.to_guild_cached(&ctx.cache)) {
+                if let Ok(permissions) = guild.member_permissions(ctx, user).await {
+                    if let Some(name) = highest(&|role| role.discord_permissions.iter().any(|p| has_discord_permission(permissions, p))) {
+                        return name;
+                    }
+                }
+            }
+        }
+        String::new()
+    }
+
+    // Get a channel's name and category for channel specific permissions
+    async fn scope(ctx: &Context, channel_id: ChannelId) -> Scope {
+        let mut scope = Scope { channel: *channel_id.as_u64(), ..Default::default() };
+        if let Ok(Channel::Guild(channel)) = channel_id.to_channel(ctx).await {
+            scope.name = channel.name.to_string();
+            scope.category = channel.parent_id.map_or(0, |c| *c.as_u64());
+        }
+        scope
+    }
+
+    // Approve or deny an application when staff click one of its buttons, if they have the permission to review it
+    async fn review_application(&self, ctx: &Context, component: &MessageComponentInteraction, approve: bool, id: u64) {
+        let roles = component.member.as_ref().map(|m| m.roles.clone()).unwrap_or_default();
+        let mut r = self.discord_role(ctx, component.guild_id, component.user.id, &roles).await;
+        if r.is_empty() {
+            r = "default".to_string();
+        }
+        let scope = Self::scope(ctx, component.channel_id).await;
+        let role = user_role(&self.config.read().unwrap(), &self.storage.read().unwrap(), &r, *component.user.id.as_u64(), &scope);
+        if !check_permission(&self.config.read().unwrap(), "applications.review".to_string(), &role) {
+            let denied = component.create_interaction_response(ctx, |r| r
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content("You don't have permission to review applications").ephemeral(true))).await;
+            if let Err(why) = denied {
+                println!("Error responding to interaction: {:?}", why);
+            }
+            return;
+        }
+
+        // Running the command can take longer than Discord waits for a response
+        if let Err(why) = component.defer(ctx).await {
+            println!("Error responding to interaction: {:?}", why);
+            return;
+        }
+        let result = match review(ctx, &self.config, &self.storage, id, approve, component.user.id).await {
+            Ok(status) => component.message.channel_id.edit_message(ctx, component.message.id, |m| m
+                .content(status)
+                .components(|c| c)).await.map(|_| ()),
+            Err(e) => component.create_followup_message(ctx, |f| f.content(e).ephemeral(true)).await.map(|_| ()),
+        };
+        if let Err(why) = result {
+            println!("Error updating application: {:?}", why);
+        }
+    }
+
+    // Look for known problems in logs that were uploaded as text files or pasted in code blocks
+    async fn analyze_logs(&self, msg: &Message, content: &str) -> String {
+        let logs = self.config.read().unwrap().logs.clone();
+        let mut texts: Vec<(String, String)> = code_blocks(content).into_iter()
+            .map(|c| ("your message".to_string(), c.to_string()))
+            .collect();
+        for attachment in &msg.attachments {
+            let name = attachment.filename.to_lowercase();
+            let is_text = name.ends_with(".log") || name.ends_with(".txt")
+                || attachment.content_type.as_ref().is_some_and(|t| t.starts_with("text/"));
+            if !is_text || attachment.size > logs.max_size {
+                continue;
+            }
+            match attachment.download().await {
+                Ok(bytes) => texts.push((attachment.filename.to_string(), String::from_utf8_lossy(&bytes).to_string())),
+                Err(why) => println!("Error downloading attachment: {:?}", why),
+            }
+        }
+        if texts.is_empty() {
+            return String::new();
+        }
+
+        // Going through large logs takes a while, so it's done off the async runtime
+        let rules = rules(&logs.rules);
+        let scan = tokio::task::spawn_blocking(move || {
+            let mut out = Vec::new();
+            for (name, text) in texts {
+                let found = analyze(&text, &rules, logs.max_matches);
+                if !found.is_empty() {
+                    out.push(format!("I found some known problems in {}:", name));
+                    out.extend(found.iter().map(|(rule, response)| format!("**{}**: {}", rule, response)));
+                }
+            }
+            out.join("\n").chars().take(1900).collect()
+        });
+        match scan.await {
+            Ok(out) => out,
+            Err(why) => {
+                println!("Error analyzing logs: {:?}", why);
+                String::new()
+            },
+        }
+    }
+
 }
 
 
 // Implements functions for events
 #[async_trait]
 impl EventHandler for Handler {
+    // Run when connected to Discord
+    async fn ready(&self, ctx: Context, _ready: Ready) {
+        if !self.monitor_started.swap(true, Ordering::SeqCst) {
+            tokio::spawn(monitor::run(ctx.http.clone(), self.config.clone(), self.storage.clone()));
+        }
+    }
+
+    // Run when a button is clicked
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::MessageComponent(component) = interaction {
+            let custom_id = component.data.custom_id.to_string();
+            let review = match (custom_id.strip_prefix(APPROVE), custom_id.strip_prefix(DENY)) {
+                (Some(id), _) => id.parse::<u64>().ok().map(|id| (true, id)),
+                (_, Some(id)) => id.parse::<u64>().ok().map(|id| (false, id)),
+                _ => None,
+            };
+            if let Some((approve, id)) = review {
+                self.review_application(&ctx, &component, approve, id).await;
+            }
+        }
+    }
+
     // Run on message
     async fn message(&self, ctx: Context, msg: Message) {
         if !msg.is_own(&ctx) {
+            // Answers to the questions of a whitelist application aren't handled as anything else
+            if answer(&ctx, &self.config, &self.storage, &msg).await {
+                return;
+            }
+
             // Get users permission group
-            let mut r = String::new(); 
-            // If the message is not from a webhook
-            if msg.webhook_id.is_none() {
-                if let Some(member) = &msg.member {
-                    for memberrole in &member.roles {
-                        if let Some((name, _role)) = self.config.read().unwrap().roles.iter().find(|(_name, role)|
-                        &role.id == memberrole.as_u64()) {
-                                r = name.to_string();
-                                break;
+            let mut r = String::new();
+            // The user whose overrides and grants apply
+            let mut user = msg.author.id;
+            // Find the player if the message came from Minecraft through a bridge
+            let bridged = bridged_player(&self.config.read().unwrap(), &msg);
+            // The name used for the user in responses and cooldowns
+            let username = bridged.as_ref().map_or(msg.author.name.to_string(), |b| b.player.to_string());
+
+            if let Some(b) = &bridged {
+                // If the player linked their Discord account, use the roles of that account
+                let linked = linked_user(&self.config.read().unwrap(), &self.storage.read().unwrap(), &b.player).map(UserId);
+                if let Some(id) = linked {
+                    if let Some(guild) = msg.guild_id {
+                        if let Ok(member) = guild.member(&ctx, id).await {
+                            user = id;
+                            r = self.discord_role(&ctx, msg.guild_id, id, &member.roles).await;
                         }
                     }
                 }
+
+                // Otherwise a role with the same name as the rank
+                if r.is_empty() && !b.rank.is_empty() {
+                    if let Some(name) = self.config.read().unwrap().roles.keys().find(|name| name.eq_ignore_ascii_case(&b.rank)) {
+                        r = name.to_string();
+                    }
+                }
+            }
+
+            // If the message is not from a webhook
+            if msg.webhook_id.is_none() {
+                if bridged.is_none() {
+                    let roles = msg.member.as_ref().map(|m| m.roles.clone()).unwrap_or_default();
+                    r = self.discord_role(&ctx, msg.guild_id, user, &roles).await;
+                }
             // If the message is from a webhook
-            } else {    
+            } else if r.is_empty() {
                 for (name, role) in &self.config.read().unwrap().roles {
                     if !role.webhook_regex.is_empty() &&  Regex::new(&role.webhook_regex).unwrap().is_match(&msg.author.name) {
                         r = name.to_string();
                         break;
                     }
                 }
-                
             };
 
             // Fall back to default role
@@ -108,11 +326,24 @@ impl EventHandler for Handler {
                 r = "default".to_string();
             }
 
-            // Set role
-            let role = self.config.read().unwrap().roles.get(&r).unwrap().clone(); 
+            // Get the channel the message was sent in for channel specific permissions
+            let scope = Self::scope(&ctx, msg.channel_id).await;
+
+            // Set role, with the user's own overrides and grants on top
+            let role = user_role(&self.config.read().unwrap(), &self.storage.read().unwrap(), &r, *user.as_u64(), &scope);
 
+            // Bridges can put the player's name in front of their message, only use what they said
+            let message = bridged.as_ref().and_then(|b| b.message.clone()).unwrap_or_else(|| msg.content.to_string());
+            // The linked Minecraft player and Discord account, for responses
+            let (player, discord) = {
+                let (config, storage) = (self.config.read().unwrap(), self.storage.read().unwrap());
+                match &bridged {
+                    Some(b) => (b.player.to_string(), linked_user(&config, &storage, &b.player).map_or(String::new(), |id| format!("<@{}>", id))),
+                    None => (linked_player(&config, &storage, *msg.author.id.as_u64()).unwrap_or_default(), format!("<@{}>", msg.author.id)),
+                }
+            };
             // Trim specified regex from messages
-            let content = Regex::new(&self.config.read().unwrap().trim_regex).unwrap().replace_all(&msg.content, "");
+            let content = Regex::new(&self.config.read().unwrap().trim_regex).unwrap().replace_all(&message, "");
 
             // Create string that will be the output
             let mut reply = String::new();
@@ -134,18 +365,29 @@ impl EventHandler for Handler {
                     }
                 }
                 
+                // Bridged players can have their own cooldowns, so one player can't hold up the rest
+                let cooldown = if bridged.is_some() && self.config.read().unwrap().per_player_cooldowns {
+                    format!("{}:{}", username.to_lowercase(), command)
+                } else {
+                    command.to_string()
+                };
+
                 // Check if command isn't on cooldown or user bypasses cooldown 
-                if check_permission(&self.config.read().unwrap(), "bypass.cooldown".to_string(), &role) || !self.command_cooldowns.read().unwrap().contains_key(&command.to_string()) || 
-                self.command_cooldowns.read().unwrap().get(&command.to_string()).unwrap().elapsed().as_secs() > self.config.read().unwrap().command_cooldown {
+                if check_permission(&self.config.read().unwrap(), "bypass.cooldown".to_string(), &role) || !self.command_cooldowns.read().unwrap().contains_key(&cooldown) || 
+                self.command_cooldowns.read().unwrap().get(&cooldown).unwrap().elapsed().as_secs() > self.config.read().unwrap().command_cooldown {
                     // Check if the command has a response
                     match response(self.config.clone(), &role, command.to_string()) {
                         // If the command has a response, set the reply and cooldown
                         Ok(r) => {
                             reply = r
-                            .replace("%username%", &msg.author.name)
+                            .replace("%username%", &username)
+                            .replace("%rank%", bridged.as_ref().map_or("", |b| &b.rank))
+                            .replace("%server%", bridged.as_ref().map_or("", |b| &b.server))
+                            .replace("%player%", &player)
+                            .replace("%discord%", &discord)
                             .replace("%content%", &content.split_once(' ').unwrap_or_default().1.to_string());
 
-                            self.command_cooldowns.write().unwrap().insert(command.to_string(), Instant::now());
+                            self.command_cooldowns.write().unwrap().insert(cooldown.to_string(), Instant::now());
                         },
                         // If permission is denied react with an emote
                         Err(CommandError::NoPerms) => {
@@ -153,12 +395,13 @@ impl EventHandler for Handler {
                         },
                         // If there is no response matching, check if there's a utility command for it
                         Err(CommandError::NoCommand) => {
-                            if let Some(cmd) = self.registered_commands.read().unwrap().get(&command.to_string()) {
-                                match cmd.execute(self.config.clone(), &role, content.split_once(' ').unwrap_or_default().1.to_string()) {
+                            let cmd = self.registered_commands.read().unwrap().get(&command.to_string()).cloned();
+                            if let Some(cmd) = cmd {
+                                match cmd.execute_message(&ctx, &msg, self.config.clone(), &role, content.split_once(' ').unwrap_or_default().1.to_string()).await {
                                     // If the command was successful, set the reply and cooldown
                                     Ok(o) => {
                                         // Set cooldown and set reply
-                                        self.command_cooldowns.write().unwrap().insert(command.to_string(), Instant::now());
+                                        self.command_cooldowns.write().unwrap().insert(cooldown.to_string(), Instant::now());
                                         reply = o;
                                     },
                                     // If the input was invalid react with an emote
@@ -199,6 +442,11 @@ impl EventHandler for Handler {
                 }
             }
 
+            // Explain known problems in logs, if nothing else was replied
+            if reply.is_empty() && check_permission(&self.config.read().unwrap(), "logs.analyze".to_string(), &role) {
+                reply = self.analyze_logs(&msg, &content).await;
+            }
+
             if !reply.is_empty() {
                 if let Err(why) = msg.reply(&ctx, reply).await {
                     println!("Error sending message: {:?}", why);
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::Utc;
+use serenity::builder::CreateEmbed;
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, MessageId};
+use serenity::model::Timestamp;
+
+use crate::config::Config;
+use crate::storage::{Outage, Storage};
+use crate::util::duration::format_duration;
+use crate::util::ping::{ping, ServerStatus};
+
+// Only keep this many outages per server
+const MAX_OUTAGES: usize = 100;
+
+// What the monitor knows about a server between checks
+#[derive(Default)]
+struct ServerState {
+    failures: u32,
+    // When the failed checks in a row started, so an outage is counted from the first one once it is declared
+    failing_since: Option<i64>,
+    down: bool,
+    last: Option<ServerStatus>,
+}
+
+fn status_embed(states: &BTreeMap<String, ServerState>) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.title("Server status");
+    for (name, state) in states {
+        let value = match (&state.last, state.down) {
+            (Some(s), _) => format!("🟢 Online, {}/{} players\n{}", s.online, s.max, s.version),
+            (None, true) => "🔴 Offline".to_string(),
+            // Failed a few checks but not enough to be considered down yet
+            (None, false) => "🟡 Not responding".to_string(),
+        };
+        embed.field(name, value, true);
+    }
+    let colour = if states.values().any(|s| s.down) { 0xE74C3C } else { 0x2ECC71 };
+    embed.colour(colour);
+    embed.footer(|f| f.text("Last checked"));
+    embed.timestamp(Timestamp::now());
+    embed
+}
+
+// Edit the status message, or send a new one if it doesn't exist yet
+async fn update_message(http: &Http, storage: &Arc<RwLock<Storage>>, channel: ChannelId, embed: CreateEmbed) {
+    let message = storage.read().unwrap().status_message;
+    if message != 0 && channel.edit_message(http, MessageId(message), |m| m.set_embed(embed.clone())).await.is_ok() {
+        return;
+    }
+    match channel.send_message(http, |m| m.set_embed(embed)).await {
+        Ok(m) => {
+            let mut storage = storage.write().unwrap();
+            storage.status_message = *m.id.as_u64();
+            storage.save();
+        },
+        Err(why) => println!("Error sending status message: {:?}", why),
+    }
+}
+
+// Periodically ping every configured server, keeping the status message up to date and posting alerts
+pub async fn run(http: Arc<Http>, config: Arc<RwLock<Config>>, storage: Arc<RwLock<Storage>>) {
+    let mut states: BTreeMap<String, ServerState> = BTreeMap::new();
+    loop {
+        let (servers, monitor) = {
+            let c = config.read().unwrap();
+            (c.servers.clone(), c.monitor.clone())
+        };
+        if !monitor.enabled {
+            tokio::time::sleep(Duration::from_secs(monitor.interval.max(10))).await;
+            continue;
+        }
+        let now = Utc::now().timestamp();
+        // Forget servers that were removed from the config
+        states.retain(|name, _state| servers.contains_key(name));
+
+        let mut alerts = Vec::new();
+        for (name, server) in &servers {
+            let result = ping(&server.address, server.timeout).await;
+            let mut storage = storage.write().unwrap();
+            let uptime = storage.uptime.entry(name.to_string()).or_default();
+            // A server that was down when the bot stopped is still down until it responds again
+            let state = states.entry(name.to_string()).or_insert_with(|| ServerState {
+                failures: if uptime.down_since.is_some() { monitor.failures.max(1) } else { 0 },
+                failing_since: uptime.down_since,
+                down: uptime.down_since.is_some(),
+                last: None,
+            });
+            if uptime.first_check == 0 {
+                uptime.first_check = now;
+            }
+            uptime.last_check = now;
+            uptime.checks += 1;
+
+            match result {
+                Ok(status) => {
+                    uptime.online += 1;
+                    if state.down {
+                        let since = uptime.down_since.take().unwrap_or(now);
+                        uptime.outages.push(Outage { start: since, end: now });
+                        if uptime.outages.len() > MAX_OUTAGES {
+                            uptime.outages.remove(0);
+                        }
+                        alerts.push(format!("🟢 **{}** is back up after {}", name, format_duration(now - since)));
+                    }
+                    *state = ServerState { last: Some(status), ..Default::default() };
+                },
+                Err(e) => {
+                    state.failures += 1;
+                    state.last = None;
+                    let since = *state.failing_since.get_or_insert(now);
+                    // Only an outage that was declared is stored, so failures that didn't lead to one aren't downtime
+                    if !state.down && state.failures >= monitor.failures.max(1) {
+                        state.down = true;
+                        uptime.down_since = Some(since);
+                        alerts.push(format!("🔴 **{}** is down ({})", name, e));
+                    }
+                },
+            }
+        }
+        storage.read().unwrap().save();
+
+        if monitor.channel != 0 {
+            let channel = ChannelId(monitor.channel);
+            update_message(&http, &storage, channel, status_embed(&states)).await;
+            let alert_channel = if monitor.alert_channel != 0 { ChannelId(monitor.alert_channel) } else { channel };
+            for alert in alerts {
+                if let Err(why) = alert_channel.say(&http, alert).await {
+                    println!("Error sending status alert: {:?}", why);
+                }
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(monitor.interval.max(10))).await;
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+use toml::{to_string, from_str};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// A permission given to a single user, optionally expiring at a unix timestamp
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Grant {
+    pub user: u64,
+    pub perm: String,
+    pub expires: Option<i64>,
+}
+
+impl Grant {
+    pub fn is_active(&self, now: i64) -> bool {
+        match self.expires {
+            Some(expires) => expires > now,
+            None => true,
+        }
+    }
+}
+
+// A code sent to a player in game, which links their Minecraft and Discord accounts when it's used with !link
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LinkCode {
+    pub code: String,
+    pub player: String,
+    // The Discord user who asked for the code, or 0 if the player asked for it in game
+    pub user: u64,
+    pub expires: i64,
+}
+
+// Where a whitelist application is, from answering the questions to being reviewed
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApplicationStatus {
+    // Still answering the questions
+    Open,
+    // Waiting for staff to review it
+    Pending,
+    Approved,
+    Denied,
+}
+
+impl ApplicationStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+        }
+    }
+}
+
+// An answer to one of the questions of an application, with the question as it was asked
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Answer {
+    pub question: String,
+    pub answer: String,
+}
+
+// A whitelist application made with !apply
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Application {
+    pub id: u64,
+    pub user: u64,
+    pub player: String,
+    pub guild: u64,
+    pub status: ApplicationStatus,
+    // The DM or thread the questions are asked in
+    pub channel: u64,
+    // The message in the staff channel with the buttons to review it
+    pub message: u64,
+    pub created: i64,
+    // Who reviewed it and when, or 0 while it hasn't been reviewed
+    pub reviewer: u64,
+    pub reviewed: i64,
+    pub answers: Vec<Answer>,
+}
+
+// A period of time a server was down, as unix timestamps
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Outage {
+    pub start: i64,
+    pub end: i64,
+}
+
+// The history of a server's status, as seen by the status monitor
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Uptime {
+    pub first_check: i64,
+    pub last_check: i64,
+    pub checks: u64,
+    pub online: u64,
+    // When the server went down, if it is down right now, set once enough checks failed in a row to declare an outage
+    pub down_since: Option<i64>,
+    pub outages: Vec<Outage>,
+}
+
+impl Uptime {
+    // How many seconds the server was down in the period from start until now
+    pub fn downtime_since(&self, start: i64, now: i64) -> i64 {
+        let mut down: i64 = self.outages.iter()
+            .map(|o| (o.end.min(now) - o.start.max(start)).max(0))
+            .sum();
+        if let Some(since) = self.down_since {
+            down += (now - since.max(start)).max(0);
+        }
+        down
+    }
+}
+
+// Struct of everything the bot keeps track of between restarts
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Storage {
+    // The message the status monitor keeps up to date
+    pub status_message: u64,
+    pub grants: Vec<Grant>,
+    // Left out while empty, as TOML can't have a plain value after the grants table
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub link_codes: Vec<LinkCode>,
+    pub uptime: BTreeMap<String, Uptime>,
+    // Minecraft players linked to Discord user IDs with !link
+    pub links: BTreeMap<String, u64>,
+    // Left out while empty for the same reason as link_codes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applications: Vec<Application>,
+}
+
+impl Storage {
+    pub fn get() -> Self {
+        let path = PathBuf::from("./storage.toml");
+        match read_to_string(&path) {
+            Ok(f) => from_str(&f).expect("Failed to parse storage.toml"),
+            // Nothing has been stored yet
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let path = PathBuf::from("./storage.toml");
+        let out = to_string(self).expect("Failed to convert to TOML format");
+        if let Err(e) = write(path, out) {
+            println!("Failed to write storage.toml: {}", e);
+        }
+    }
+}
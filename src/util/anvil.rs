@@ -0,0 +1,163 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use toml::from_str;
+
+// Costs of this many levels or more are "Too Expensive!" in survival
+pub const TOO_EXPENSIVE: u32 = 40;
+
+// An item that can be enchanted in an anvil
+#[derive(Deserialize)]
+pub struct Tool {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+// An enchantment with the multiplier the anvil uses for it when it comes from a book
+#[derive(Deserialize)]
+pub struct Enchantment {
+    pub name: String,
+    pub max_level: u32,
+    pub multiplier: u32,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub tools: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EnchantmentTable {
+    pub tools: Vec<Tool>,
+    pub enchantments: Vec<Enchantment>,
+    pub exclusive: Vec<Vec<String>>,
+}
+
+impl EnchantmentTable {
+    // Whether two enchantments can't be on the same item
+    pub fn exclusive(&self, a: &str, b: &str) -> bool {
+        self.exclusive.iter().any(|group| group.iter().any(|e| e == a) && group.iter().any(|e| e == b))
+    }
+}
+
+// Get the bundled enchantment table, parsed the first time it's needed
+pub fn enchantments() -> &'static EnchantmentTable {
+    static TABLE: OnceLock<EnchantmentTable> = OnceLock::new();
+    TABLE.get_or_init(|| from_str(include_str!("../../assets/enchantments.toml")).expect("Failed to parse enchantments.toml"))
+}
+
+// The extra cost of an item or book that has been through an anvil this many times
+pub fn prior_work_penalty(work: u32) -> u32 {
+    (1 << work) - 1
+}
+
+// Something put in the anvil, either the item or a book with some of the enchantments, as a bitmask of them
+#[derive(Clone, Copy)]
+pub enum Piece {
+    Item(u32),
+    Book(u32),
+}
+
+// One use of the anvil, the target on the left and the book on the right
+pub struct Step {
+    pub target: Piece,
+    pub sacrifice: u32,
+    pub cost: u32,
+}
+
+// The best way found to make something, and what it was made from
+#[derive(Clone, Copy)]
+struct Best {
+    total: u32,
+    from: Option<(u32, u32, u32, u32)>,
+}
+
+// Collect the steps that made something, the steps for its parts first
+fn walk(table: &[Vec<Option<Best>>], books: &[Vec<Option<Best>>], item: bool, mask: u32, work: u32, value: &dyn Fn(u32) -> u32, steps: &mut Vec<Step>) {
+    let Some(Best { from: Some((target, wt, sacrifice, ws)), .. }) = table[mask as usize][work as usize] else {
+        return;
+    };
+    walk(table, books, item, target, wt, value, steps);
+    walk(books, books, false, sacrifice, ws, value, steps);
+    steps.push(Step {
+        target: if item { Piece::Item(target) } else { Piece::Book(target) },
+        sacrifice,
+        cost: prior_work_penalty(wt) + prior_work_penalty(ws) + value(sacrifice),
+    });
+}
+
+fn combine(table: &mut [Vec<Option<Best>>], mask: u32, work: u32, total: u32, from: (u32, u32, u32, u32)) {
+    let slot = &mut table[mask as usize][work as usize];
+    if slot.is_none_or(|b| total < b.total) {
+        *slot = Some(Best { total, from: Some(from) });
+    }
+}
+
+// Find the cheapest order to put books with these enchantment costs (level times multiplier) onto a new item,
+// where no single step is too expensive. Returns the steps in order, the total cost and the prior work of the result.
+pub fn cheapest_order(values: &[u32]) -> Option<(Vec<Step>, u32, u32)> {
+    let n = values.len();
+    let full = (1u32 << n) - 1;
+    let works = n + 1;
+    let value = |mask: u32| (0..n).filter(|i| mask & (1 << i) != 0).map(|i| values[i]).sum::<u32>();
+
+    // The cheapest way to make a book with some of the enchantments, for every amount of prior work
+    let mut books: Vec<Vec<Option<Best>>> = vec![vec![None; works]; 1 << n];
+    for i in 0..n {
+        books[1 << i][0] = Some(Best { total: 0, from: None });
+    }
+    // Submasks are always smaller, so they are done before the masks they are part of
+    for mask in 1..=full {
+        if mask.count_ones() < 2 {
+            continue;
+        }
+        let mut target = (mask - 1) & mask;
+        while target > 0 {
+            let sacrifice = mask ^ target;
+            for wt in 0..works as u32 {
+                for ws in 0..works as u32 {
+                    let (Some(t), Some(s)) = (books[target as usize][wt as usize], books[sacrifice as usize][ws as usize]) else {
+                        continue;
+                    };
+                    let cost = prior_work_penalty(wt) + prior_work_penalty(ws) + value(sacrifice);
+                    let work = wt.max(ws) + 1;
+                    if cost < TOO_EXPENSIVE && (work as usize) < works {
+                        combine(&mut books, mask, work, t.total + s.total + cost, (target, wt, sacrifice, ws));
+                    }
+                }
+            }
+            target = (target - 1) & mask;
+        }
+    }
+
+    // The cheapest way to get some of the enchantments onto the item, adding one book at a time
+    let mut items: Vec<Vec<Option<Best>>> = vec![vec![None; works]; 1 << n];
+    items[0][0] = Some(Best { total: 0, from: None });
+    for mask in 1..=full {
+        let mut sacrifice = mask;
+        while sacrifice > 0 {
+            let target = mask ^ sacrifice;
+            for wt in 0..works as u32 {
+                for ws in 0..works as u32 {
+                    let (Some(t), Some(s)) = (items[target as usize][wt as usize], books[sacrifice as usize][ws as usize]) else {
+                        continue;
+                    };
+                    let cost = prior_work_penalty(wt) + prior_work_penalty(ws) + value(sacrifice);
+                    let work = wt.max(ws) + 1;
+                    if cost < TOO_EXPENSIVE && (work as usize) < works {
+                        combine(&mut items, mask, work, t.total + s.total + cost, (target, wt, sacrifice, ws));
+                    }
+                }
+            }
+            sacrifice = (sacrifice - 1) & mask;
+        }
+    }
+
+    // The cheapest result, and the one with the least prior work if there's a tie
+    let (work, best) = items[full as usize].iter().enumerate()
+        .filter_map(|(w, b)| b.map(|b| (w as u32, b)))
+        .min_by_key(|(w, b)| (b.total, *w))?;
+
+    let mut steps = Vec::new();
+    walk(&items, &books, true, full, work, &value, &mut steps);
+    Some((steps, best.total, work))
+}
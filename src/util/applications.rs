@@ -0,0 +1,229 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, UserId};
+
+use crate::config::Config;
+use crate::storage::{Answer, Application, ApplicationStatus, Storage};
+use crate::util::format::strip_codes;
+use crate::util::rcon::run_command;
+
+// Embed field values can hold 1024 characters and their names 256, an embed has 25 fields at most,
+// and all of the text in an embed together can't be longer than 6000 characters
+const MAX_ANSWER: usize = 1000;
+const MAX_QUESTION: usize = 250;
+const MAX_QUESTIONS: usize = 25;
+const MAX_EMBED: usize = 6000;
+
+// The custom IDs of the review buttons start with these, followed by the ID of the application
+pub const APPROVE: &str = "application:approve:";
+pub const DENY: &str = "application:deny:";
+
+// The application as it is shown to staff, with every question and answer
+pub fn application_embed(application: &Application) -> CreateEmbed {
+    let title = format!("Whitelist application #{}: {}", application.id, application.player);
+    let description = format!("From <@{}>", application.user);
+    let answers = &application.answers[..application.answers.len().min(MAX_QUESTIONS)];
+    // Long questions and answers are cut short to fit the embed's length instead of it failing to send,
+    // with the questions getting at most half of it and the answers sharing what is left
+    let available = MAX_EMBED.saturating_sub(title.chars().count() + description.chars().count());
+    let per_question = (available / 2 / answers.len().max(1)).min(MAX_QUESTION);
+    let questions: Vec<String> = answers.iter().map(|a| shorten(&a.question, per_question)).collect();
+    let used = questions.iter().map(|q| q.chars().count()).sum::<usize>();
+    let per_answer = (available.saturating_sub(used) / answers.len().max(1)).min(MAX_ANSWER);
+
+    let mut embed = CreateEmbed::default();
+    embed.title(title);
+    embed.description(description);
+    for (question, answer) in questions.iter().zip(answers) {
+        embed.field(question, shorten(&answer.answer, per_answer), false);
+    }
+    embed
+}
+
+// Cut text down to a number of characters, ending it with … if it had to be shortened
+fn shorten(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max.saturating_sub(1)).collect();
+    short.push('…');
+    short
+}
+
+// Post a finished application in the staff channel with buttons to approve or deny it
+async fn submit(ctx: &Context, config: &Arc<RwLock<Config>>, storage: &Arc<RwLock<Storage>>, application: Application) -> serenity::Result<()> {
+    let channel = ChannelId(config.read().unwrap().applications.channel);
+    let posted = channel.send_message(ctx, |m| m
+        .set_embed(application_embed(&application))
+        .components(|c| c.create_action_row(|r| r
+            .create_button(|b| b.custom_id(format!("{}{}", APPROVE, application.id)).label("Approve").style(ButtonStyle::Success))
+            .create_button(|b| b.custom_id(format!("{}{}", DENY, application.id)).label("Deny").style(ButtonStyle::Danger))))).await?;
+    {
+        let mut storage = storage.write().unwrap();
+        if let Some(a) = storage.applications.iter_mut().find(|a| a.id == application.id) {
+            a.status = ApplicationStatus::Pending;
+            a.message = *posted.id.as_u64();
+            a.created = Utc::now().timestamp();
+        }
+        storage.save();
+    }
+    ChannelId(application.channel).say(ctx, format!("Thanks! Your application for {} was sent to the staff, I'll let you know here once it has been reviewed.", application.player)).await?;
+    Ok(())
+}
+
+// Ask the next question of an application, or submit it once every question is answered
+pub async fn next_question(ctx: &Context, config: &Arc<RwLock<Config>>, storage: &Arc<RwLock<Storage>>, id: u64) -> serenity::Result<()> {
+    let Some(application) = storage.read().unwrap().applications.iter().find(|a| a.id == id).cloned() else {
+        return Ok(());
+    };
+    let questions = config.read().unwrap().applications.questions.clone();
+    let total = questions.len().min(MAX_QUESTIONS);
+    let asked = application.answers.len();
+    if asked < total {
+        ChannelId(application.channel).say(ctx, format!("**Question {}/{}**: {}", asked + 1, total, questions[asked])).await?;
+        return Ok(());
+    }
+    submit(ctx, config, storage, application).await
+}
+
+// Take a message as the answer to the next question if it was sent where the author's open application is being filled in,
+// returning whether it was
+pub async fn answer(ctx: &Context, config: &Arc<RwLock<Config>>, storage: &Arc<RwLock<Storage>>, msg: &Message) -> bool {
+    let mut text = msg.content.trim().to_string();
+    for attachment in &msg.attachments {
+        text = format!("{}\n{}", text, attachment.url).trim().to_string();
+    }
+
+    let id = {
+        let mut questions = config.read().unwrap().applications.questions.clone();
+        // Only as many questions are asked as fit in the embed that is sent to the staff
+        questions.truncate(MAX_QUESTIONS);
+        let mut storage = storage.write().unwrap();
+        let open = storage.applications.iter_mut().find(|a| a.status == ApplicationStatus::Open
+            && a.user == *msg.author.id.as_u64() && a.channel == *msg.channel_id.as_u64());
+        let Some(application) = open else {
+            return false;
+        };
+        // Anything sent after the last question, like when submitting failed, tries to submit again
+        if let Some(question) = questions.get(application.answers.len()) {
+            if text.is_empty() {
+                return true;
+            }
+            application.answers.push(Answer { question: question.to_string(), answer: text });
+        }
+        let id = application.id;
+        storage.save();
+        id
+    };
+    if let Err(why) = next_question(ctx, config, storage, id).await {
+        println!("Error continuing application: {:?}", why);
+        let _ = msg.channel_id.say(ctx, "Something went wrong sending your application, send anything here to try again.").await;
+    }
+    true
+}
+
+// Approve or deny a pending application, returning what happened to show on the application in the staff channel
+pub async fn review(ctx: &Context, config: &Arc<RwLock<Config>>, storage: &Arc<RwLock<Storage>>, id: u64, approve: bool, reviewer: UserId) -> Result<String, String> {
+    let application = storage.read().unwrap().applications.iter().find(|a| a.id == id).cloned()
+        .ok_or_else(|| "This application doesn't exist anymore".to_string())?;
+    if application.status != ApplicationStatus::Pending {
+        return Err(format!("This application was already {}", application.status.name()));
+    }
+    let (rcon, settings) = {
+        let config = config.read().unwrap();
+        (config.rcon.clone(), config.applications.clone())
+    };
+
+    let mut lines = Vec::new();
+    if approve {
+        // The application stays pending if the player can't be whitelisted, so it can be approved again
+        if !settings.command.is_empty() {
+            if rcon.password.is_empty() {
+                return Err("RCON needs to be set up to approve applications".to_string());
+            }
+            let command = settings.command.replace("%player%", &application.player);
+            let output = run_command(&rcon, &command).await.map_err(|e| format!("Couldn't run `{}`: {}", command, e))?;
+            lines.push(format!("Server: {}", strip_codes(&output)));
+        }
+        if settings.role != 0 && application.guild != 0 {
+            if let Err(e) = ctx.http.add_member_role(application.guild, application.user, settings.role, Some("Whitelist application approved")).await {
+                lines.push(format!("Couldn't give the role: {}", e));
+            }
+        }
+    }
+
+    let status = if approve { ApplicationStatus::Approved } else { ApplicationStatus::Denied };
+    {
+        let mut storage = storage.write().unwrap();
+        match storage.applications.iter_mut().find(|a| a.id == id) {
+            // Someone else could have reviewed it while the command ran
+            Some(a) if a.status == ApplicationStatus::Pending => {
+                a.status = status;
+                a.reviewer = *reviewer.as_u64();
+                a.reviewed = Utc::now().timestamp();
+            },
+            _ => return Err("This application was already reviewed".to_string()),
+        }
+        storage.save();
+    }
+    if let Err(why) = ChannelId(application.channel).say(ctx, format!("Your application for {} was {}.", application.player, status.name())).await {
+        println!("Error telling applicant about their application: {:?}", why);
+    }
+
+    lines.insert(0, format!("{} by <@{}>", if approve { "Approved" } else { "Denied" }, reviewer));
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn application(questions: usize, question: &str, answer: &str) -> Application {
+        Application {
+            id: 1,
+            user: 123456781234567812,
+            player: "Steve".to_string(),
+            guild: 0,
+            status: ApplicationStatus::Pending,
+            channel: 0,
+            message: 0,
+            created: 0,
+            reviewer: 0,
+            reviewed: 0,
+            answers: (0..questions).map(|_| Answer { question: question.to_string(), answer: answer.to_string() }).collect(),
+        }
+    }
+
+    // The length of all text in the embed, the way Discord limits it
+    fn embed_length(embed: &CreateEmbed) -> usize {
+        let text = |v: &serde_json::Value| v.as_str().map_or(0, |s| s.chars().count());
+        let fields = embed.0.get("fields").and_then(|f| f.as_array()).cloned().unwrap_or_default();
+        text(&embed.0["title"]) + text(&embed.0["description"])
+            + fields.iter().map(|f| text(&f["name"]) + text(&f["value"])).sum::<usize>()
+    }
+
+    #[test]
+    fn short_answers_are_kept() {
+        let embed = application_embed(&application(3, "Why do you want to join?", "To build with friends"));
+        let fields = embed.0["fields"].as_array().unwrap();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0]["value"], "To build with friends");
+    }
+
+    #[test]
+    fn long_applications_fit_in_an_embed() {
+        for (questions, question, answer) in [(25, "Q", "é".repeat(2000)), (30, &*"Why?".repeat(100), "a".repeat(1000)), (5, "Q", "a".repeat(2000))] {
+            let embed = application_embed(&application(questions, question, &answer));
+            let fields = embed.0["fields"].as_array().unwrap();
+            assert!(fields.len() <= MAX_QUESTIONS);
+            assert!(embed_length(&embed) <= MAX_EMBED, "{} questions came to {} characters", questions, embed_length(&embed));
+            assert!(fields.iter().all(|f| f["value"].as_str().unwrap().chars().count() <= 1024 && f["name"].as_str().unwrap().chars().count() <= 256));
+            assert!(fields[0]["value"].as_str().unwrap().ends_with('…'));
+        }
+    }
+}
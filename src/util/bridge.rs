@@ -0,0 +1,75 @@
+use regex::Regex;
+use serenity::model::channel::Message;
+
+use crate::config::Config;
+use crate::util::format::strip_codes;
+
+// Who sent a message through a Minecraft bridge, and what they said
+#[derive(Default)]
+pub struct Bridged {
+    pub player: String,
+    pub rank: String,
+    pub server: String,
+    // The chat message without the player's name and rank, if the bridge puts them in the content
+    pub message: Option<String>,
+}
+
+// Get the text of a field of the message, for bridges to match against
+fn field(msg: &Message, name: &str) -> Option<String> {
+    let embed = msg.embeds.first();
+    match name {
+        "content" => Some(msg.content.to_string()),
+        "author" => Some(msg.author.name.to_string()),
+        "embed_author" => embed?.author.as_ref().map(|a| a.name.to_string()),
+        "embed_title" => embed?.title.clone(),
+        "embed_description" => embed?.description.clone(),
+        "embed_footer" => embed?.footer.as_ref().map(|f| f.text.to_string()),
+        _ => None,
+    }
+}
+
+// Apply a regex to some text, filling in the player's details from its named groups
+fn capture(bridged: &mut Bridged, regex: &str, text: &str) -> bool {
+    let caps = match Regex::new(regex).ok().and_then(|r| r.captures(text)) {
+        Some(c) => c,
+        None => return false,
+    };
+    let group = |name: &str| caps.name(name).map(|m| m.as_str().trim().to_string());
+    // Names and ranks are often coloured, but the message is left alone as & is also used in chat
+    if let Some(player) = group("player") {
+        bridged.player = strip_codes(&player);
+    }
+    if let Some(rank) = group("rank") {
+        bridged.rank = strip_codes(&rank);
+    }
+    if let Some(server) = group("server") {
+        bridged.server = strip_codes(&server);
+    }
+    if let Some(message) = group("message") {
+        bridged.message = Some(message);
+    }
+    true
+}
+
+// Find the player behind a message from a configured bridge, or a webhook matching the webhook extractor
+pub fn bridged_player(config: &Config, msg: &Message) -> Option<Bridged> {
+    let webhook = msg.webhook_id.map(|w| *w.as_u64());
+    let mut bridged = Bridged::default();
+
+    let bridge = config.bridges.values().find(|b| b.source != 0 &&
+        (b.source == *msg.author.id.as_u64() || Some(b.source) == webhook));
+    if let Some(bridge) = bridge {
+        for (name, regex) in &bridge.patterns {
+            if let Some(text) = field(msg, name) {
+                capture(&mut bridged, regex, &text);
+            }
+        }
+    } else if webhook.is_some() && !config.webhook_extractor.is_empty() {
+        capture(&mut bridged, &config.webhook_extractor, &msg.author.name);
+    }
+
+    if bridged.player.is_empty() {
+        return None;
+    }
+    Some(bridged)
+}
@@ -0,0 +1,150 @@
+use crate::util::png::encode_rgb;
+
+// The base colours blocks have on maps, with some blocks that have them
+pub const MAP_COLOURS: [(&str, u32); 61] = [
+    ("Grass (grass block, slime block)", 0x7FB238),
+    ("Sand (sand, birch planks, sandstone)", 0xF7E9A3),
+    ("Wool (cobweb, mushroom stem)", 0xC7C7C7),
+    ("Fire (TNT, redstone block, lava)", 0xFF0000),
+    ("Ice (ice, packed ice, blue ice)", 0xA0A0FF),
+    ("Metal (block of iron, anvil, lantern)", 0xA7A7A7),
+    ("Plant (leaves, grass, saplings)", 0x007C00),
+    ("Snow (snow block, white wool, white concrete)", 0xFFFFFF),
+    ("Clay (clay, infested blocks)", 0xA4A8B8),
+    ("Dirt (dirt, coarse dirt, granite)", 0x976D4D),
+    ("Stone (stone, cobblestone, andesite)", 0x707070),
+    ("Water (water, kelp, seagrass)", 0x4040FF),
+    ("Wood (oak planks, oak log sides)", 0x8F7748),
+    ("Quartz (block of quartz, diorite, sea lantern)", 0xFFFCF5),
+    ("Orange (orange wool, pumpkin, acacia planks)", 0xD87F33),
+    ("Magenta (magenta wool, purpur block)", 0xB24CD8),
+    ("Light blue (light blue wool, light blue concrete)", 0x6699D8),
+    ("Yellow (yellow wool, hay bale, sponge)", 0xE5E533),
+    ("Lime (lime wool, melon)", 0x7FCC19),
+    ("Pink (pink wool, pink concrete)", 0xF27FA5),
+    ("Gray (gray wool, gray concrete)", 0x4C4C4C),
+    ("Light gray (light gray wool, light gray concrete)", 0x999999),
+    ("Cyan (cyan wool, prismarine)", 0x4C7F99),
+    ("Purple (purple wool, mycelium, amethyst)", 0x7F3FB2),
+    ("Blue (blue wool, blue concrete)", 0x334CB2),
+    ("Brown (brown wool, dark oak planks, soul sand)", 0x664C33),
+    ("Green (green wool, green concrete)", 0x667F33),
+    ("Red (red wool, nether wart block, bricks)", 0x993333),
+    ("Black (black wool, obsidian, coal block)", 0x191919),
+    ("Gold (block of gold, light weighted pressure plate)", 0xFAEE4D),
+    ("Diamond (block of diamond, prismarine bricks)", 0x5CDBD5),
+    ("Lapis (block of lapis lazuli)", 0x4A80FF),
+    ("Emerald (block of emerald)", 0x00D93A),
+    ("Podzol (podzol, spruce planks)", 0x815631),
+    ("Nether (netherrack, nether quartz ore)", 0x700200),
+    ("White terracotta (white terracotta, calcite)", 0xD1B1A1),
+    ("Orange terracotta (orange terracotta, red sandstone)", 0x9F5224),
+    ("Magenta terracotta (magenta terracotta)", 0x95576C),
+    ("Light blue terracotta (light blue terracotta)", 0x706C8A),
+    ("Yellow terracotta (yellow terracotta)", 0xBA8524),
+    ("Lime terracotta (lime terracotta)", 0x677535),
+    ("Pink terracotta (pink terracotta)", 0xA04D4E),
+    ("Gray terracotta (gray terracotta, tuff)", 0x392923),
+    ("Light gray terracotta (light gray terracotta, mud bricks)", 0x876B62),
+    ("Cyan terracotta (cyan terracotta, mud)", 0x575C5C),
+    ("Purple terracotta (purple terracotta)", 0x7A4958),
+    ("Blue terracotta (blue terracotta)", 0x4C3E5C),
+    ("Brown terracotta (brown terracotta, dripstone)", 0x4C3223),
+    ("Green terracotta (green terracotta)", 0x4C522A),
+    ("Red terracotta (red terracotta)", 0x8E3C2E),
+    ("Black terracotta (black terracotta)", 0x251610),
+    ("Crimson nylium (crimson nylium)", 0xBD3031),
+    ("Crimson stem (crimson stem, crimson planks)", 0x943F61),
+    ("Crimson hyphae (crimson hyphae)", 0x5C191D),
+    ("Warped nylium (warped nylium)", 0x167E86),
+    ("Warped stem (warped stem, warped planks)", 0x3A8E8C),
+    ("Warped hyphae (warped hyphae)", 0x562C3E),
+    ("Warped wart block (warped wart block)", 0x14B485),
+    ("Deepslate (deepslate, cobbled deepslate)", 0x646464),
+    ("Raw iron (block of raw iron)", 0xD8AF93),
+    ("Glow lichen (glow lichen, verdant froglight)", 0x7FA796),
+];
+
+// How much a map colour is darkened for blocks that are lower than, level with or higher than the block north of them
+pub const MAP_SHADES: [(&str, u32); 3] = [
+    ("dark, placed lower than the block north of it", 180),
+    ("flat, placed level with the block north of it", 220),
+    ("light, placed higher than the block north of it", 255),
+];
+
+// The colour names dyes, wool, concrete and terracotta use, in the order of the tables below
+pub const DYE_NAMES: [&str; 16] = [
+    "White", "Orange", "Magenta", "Light Blue", "Yellow", "Lime", "Pink", "Gray",
+    "Light Gray", "Cyan", "Purple", "Blue", "Brown", "Green", "Red", "Black",
+];
+
+// The colours dyes give leather armour, banners and fireworks
+pub const DYES: [u32; 16] = [
+    0xF9FFFE, 0xF9801D, 0xC74EBD, 0x3AB3DA, 0xFED83D, 0x80C71F, 0xF38BAA, 0x474F52,
+    0x9D9D97, 0x169C9C, 0x8932B8, 0x3C44AA, 0x835432, 0x5E7C16, 0xB02E26, 0x1D1D21,
+];
+
+// The average colours of the block textures
+pub const WOOL: [u32; 16] = [
+    0xE9ECEC, 0xF07613, 0xBD44B3, 0x3AAFD9, 0xF8C527, 0x70B919, 0xED8DAC, 0x3E4447,
+    0x8E8E86, 0x158991, 0x792AAC, 0x35399D, 0x724728, 0x546D1B, 0xA12722, 0x141519,
+];
+pub const CONCRETE: [u32; 16] = [
+    0xCFD5D6, 0xE06100, 0xA9309F, 0x2389C6, 0xF0AF15, 0x5EA818, 0xD5658E, 0x36393D,
+    0x7D7D73, 0x157788, 0x64209C, 0x2C2E8F, 0x603B1F, 0x495B24, 0x8E2020, 0x080A0F,
+];
+pub const TERRACOTTA: [u32; 16] = [
+    0xD1B2A1, 0xA15325, 0x95586C, 0x716C89, 0xBA8523, 0x677534, 0xA14E4E, 0x392A23,
+    0x876A61, 0x565B5B, 0x764656, 0x4A3B5B, 0x4D3323, 0x4C532A, 0x8F3D2E, 0x251610,
+];
+
+// The size of each swatch in the image, in pixels
+const SWATCH: u32 = 64;
+
+// Convert a colour to CIELAB, where the distance between colours is close to how different they look
+fn lab(rgb: u32) -> [f64; 3] {
+    let linear = |c: u32| {
+        let c = (c & 0xFF) as f64 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    let (r, g, b) = (linear(rgb >> 16), linear(rgb >> 8), linear(rgb));
+    // XYZ relative to the D65 white point
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f64| if t > 0.008856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+    [116.0 * f(y) - 16.0, 500.0 * (f(x) - f(y)), 200.0 * (f(y) - f(z))]
+}
+
+// How different two colours look, where about 2.3 is just noticeable
+pub fn distance(a: u32, b: u32) -> f64 {
+    let (a, b) = (lab(a), lab(b));
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f64>().sqrt()
+}
+
+// Find the index of the colour in a list that looks closest to a colour
+pub fn nearest(rgb: u32, colours: impl Iterator<Item = u32>) -> Option<(usize, f64)> {
+    colours.enumerate()
+        .map(|(i, c)| (i, distance(rgb, c)))
+        .min_by(|(_i, a), (_j, b)| a.total_cmp(b))
+}
+
+// A map colour darkened for a shade
+pub fn shade(rgb: u32, multiplier: u32) -> u32 {
+    let channel = |shift: u32| ((rgb >> shift & 0xFF) * multiplier / 255) << shift;
+    channel(16) | channel(8) | channel(0)
+}
+
+// Draw a row of square swatches of colours as a PNG
+pub fn swatches(colours: &[u32]) -> Vec<u8> {
+    let width = SWATCH * colours.len() as u32;
+    let mut pixels = Vec::with_capacity((width * SWATCH * 3) as usize);
+    for _row in 0..SWATCH {
+        for colour in colours {
+            for _column in 0..SWATCH {
+                pixels.extend_from_slice(&[(colour >> 16) as u8, (colour >> 8) as u8, *colour as u8]);
+            }
+        }
+    }
+    encode_rgb(width, SWATCH, &pixels)
+}
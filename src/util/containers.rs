@@ -0,0 +1,64 @@
+use crate::config::{Config, Container};
+use crate::util::items::closest;
+
+// Something items can be counted in, like a chest or a stack, with how many items fit in it
+pub struct Unit {
+    pub name: String,
+    pub items: u64,
+}
+
+// Containers can be nested, but not endlessly
+const MAX_DEPTH: usize = 8;
+
+// Find a configured container by its name or one of its aliases, allowing plurals and small typos
+pub fn find_container<'a>(config: &'a Config, name: &str) -> Option<(&'a str, &'a Container)> {
+    let options = config.containers.iter().flat_map(|(key, container)|
+        std::iter::once(key.as_str()).chain(container.aliases.iter().map(|a| a.as_str()))
+            .map(move |n| (n, (key.as_str(), container))));
+    closest(name, options)
+}
+
+// Get the units to break items down into for a container, from the container itself down to stacks
+pub fn units(config: &Config, container: &str, stack: u64) -> Option<Vec<Unit>> {
+    // Find the chain of containers inside each other
+    let mut chain: Vec<(&str, &Container)> = Vec::new();
+    let mut next = container;
+    while !next.is_empty() && chain.len() < MAX_DEPTH {
+        let (key, c) = config.containers.get_key_value(next)?;
+        chain.push((key, c));
+        next = &c.contains;
+    }
+
+    // Work out the sizes from the inside out
+    let mut units = Vec::new();
+    let mut size = stack;
+    if stack > 1 {
+        units.push(Unit { name: "stack".to_string(), items: stack });
+    }
+    for (key, c) in chain.iter().rev() {
+        size = size.saturating_mul(c.slots.max(1));
+        units.push(Unit { name: key.replace('_', " "), items: size });
+    }
+    units.reverse();
+    Some(units)
+}
+
+fn plural(amount: u64) -> &'static str {
+    if amount == 1 { "" } else { "s" }
+}
+
+// Describe how an amount of items breaks down into units, like "2 shulkers plus 3 stacks and 5 items left over"
+pub fn breakdown(items: u64, units: &[Unit]) -> String {
+    let mut left = items;
+    let mut parts = Vec::new();
+    for unit in units {
+        let count = left / unit.items;
+        left %= unit.items;
+        parts.push(format!("{} {}{}", count, unit.name, plural(count)));
+    }
+    let items = format!("{} item{} left over", left, plural(left));
+    if parts.is_empty() {
+        return items;
+    }
+    format!("{} and {}", parts.join(" plus "), items)
+}
@@ -0,0 +1,44 @@
+// Parse a duration like 2h, 30m or 1d12h into seconds
+pub fn parse_duration(input: &str) -> Option<i64> {
+    let mut total: i64 = 0;
+    let mut number = String::new();
+    for c in input.trim().to_lowercase().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+        } else {
+            let unit = match c {
+                's' => 1,
+                'm' => 60,
+                'h' => 3600,
+                'd' => 86400,
+                'w' => 604800,
+                _ => return None,
+            };
+            let amount = number.parse::<i64>().ok()?;
+            total = total.checked_add(amount.checked_mul(unit)?)?;
+            number.clear();
+        }
+    }
+    // Every number needs a unit after it
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+    Some(total)
+}
+
+// Format an amount of seconds as the largest fitting units, like 1d 2h 5m
+pub fn format_duration(seconds: i64) -> String {
+    let mut parts = Vec::new();
+    let mut left = seconds.max(0);
+    for (unit, size) in [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)] {
+        if left >= size {
+            parts.push(format!("{}{}", left / size, unit));
+            left %= size;
+        }
+    }
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
@@ -0,0 +1,326 @@
+// The largest number a fraction can be worked with exactly as a 64-bit float
+const MAX_EXACT: f64 = 9007199254740992.0;
+
+const TOO_LARGE: &str = "That number is too large";
+
+// Brackets and signs can only be nested this deep, as every level is parsed with recursion
+const MAX_DEPTH: usize = 64;
+
+// A value that stays a whole number while it can, so large amounts are exact, and becomes a fraction when it has to
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Number {
+    Whole(i64),
+    Fraction(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Whole(n) => n as f64,
+            Number::Fraction(f) => f,
+        }
+    }
+
+    // The value as a float, if it fits without being rounded
+    fn exact(self) -> Result<f64, String> {
+        let exact = match self {
+            Number::Whole(n) => n.unsigned_abs() <= MAX_EXACT as u64,
+            Number::Fraction(f) => f.abs() <= MAX_EXACT,
+        };
+        if !exact {
+            return Err(TOO_LARGE.to_string());
+        }
+        Ok(self.as_f64())
+    }
+
+    // A fraction that works out to a whole number becomes one again
+    fn fraction(value: f64) -> Result<Number, String> {
+        if !value.is_finite() || value.abs() > MAX_EXACT {
+            return Err(TOO_LARGE.to_string());
+        }
+        if value.fract() == 0.0 {
+            return Ok(Number::Whole(value as i64));
+        }
+        Ok(Number::Fraction(value))
+    }
+
+    // Work out an operation on whole numbers without overflowing, or on floats if either one is a fraction
+    fn apply(self, other: Number, whole: fn(i64, i64) -> Option<i64>, fraction: fn(f64, f64) -> f64) -> Result<Number, String> {
+        match (self, other) {
+            (Number::Whole(a), Number::Whole(b)) => whole(a, b).map(Number::Whole).ok_or_else(|| TOO_LARGE.to_string()),
+            (a, b) => Number::fraction(fraction(a.exact()?, b.exact()?)),
+        }
+    }
+
+    fn add(self, other: Number) -> Result<Number, String> {
+        self.apply(other, i64::checked_add, |a, b| a + b)
+    }
+
+    fn sub(self, other: Number) -> Result<Number, String> {
+        self.apply(other, i64::checked_sub, |a, b| a - b)
+    }
+
+    fn mul(self, other: Number) -> Result<Number, String> {
+        self.apply(other, i64::checked_mul, |a, b| a * b)
+    }
+
+    fn div(self, other: Number) -> Result<Number, String> {
+        match (self, other) {
+            (_, b) if b.as_f64() == 0.0 => Err("Can't divide by zero".to_string()),
+            // Whole numbers that divide evenly stay exact
+            (Number::Whole(a), Number::Whole(b)) if a.checked_rem(b) == Some(0) => Ok(Number::Whole(a.checked_div(b).ok_or_else(|| TOO_LARGE.to_string())?)),
+            (a, b) => Number::fraction(a.exact()? / b.exact()?),
+        }
+    }
+
+    fn neg(self) -> Result<Number, String> {
+        match self {
+            Number::Whole(n) => n.checked_neg().map(Number::Whole).ok_or_else(|| TOO_LARGE.to_string()),
+            Number::Fraction(f) => Ok(Number::Fraction(-f)),
+        }
+    }
+}
+
+// Parses amounts like 3*64+5, 1.5k, -5 or 2 stacks 10, where stacks and shulker boxes use the stack size if given
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    stack: Option<i64>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    // Parse a part that is nested in another, giving up when they are nested too deeply
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err("That expression is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    // Position for error messages, counted in characters from 1
+    fn position(&self) -> usize {
+        self.input[..self.pos].chars().count() + 1
+    }
+
+    // The word at the current position, if it starts with a letter
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let end = rest.find(|c: char| !c.is_alphabetic()).unwrap_or(rest.len());
+        &rest[..end]
+    }
+
+    // How much a unit word multiplies a number by
+    fn unit(&self, word: &str) -> Option<Number> {
+        match (word.to_lowercase().as_str(), self.stack) {
+            ("k", _) => Some(Number::Whole(1_000)),
+            ("m", _) => Some(Number::Whole(1_000_000)),
+            ("st" | "stack" | "stacks", Some(stack)) => Some(Number::Whole(stack)),
+            // Not "shulker" on its own, as that starts item names like shulker shell
+            ("sb" | "shulkers", Some(stack)) => stack.checked_mul(27).map(Number::Whole),
+            _ => None,
+        }
+    }
+
+    // Check if a number or bracket comes next, without moving
+    fn at_value(&mut self) -> bool {
+        matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '.' || c == '(')
+    }
+
+    // expression: term, with + or - between terms
+    fn expression(&mut self) -> Result<Number, String> {
+        let (mut value, mut had_unit) = self.term()?;
+        loop {
+            match self.peek() {
+                Some('+') => {
+                    self.pos += 1;
+                    value = value.add(self.term()?.0)?;
+                },
+                Some('-') => {
+                    self.pos += 1;
+                    value = value.sub(self.term()?.0)?;
+                },
+                // Amounts with units can follow each other, like 2 stacks 10
+                _ if had_unit && self.at_value() => {
+                    let (next, unit) = self.term()?;
+                    value = value.add(next)?;
+                    had_unit = unit;
+                    continue;
+                },
+                _ => return Ok(value),
+            }
+            had_unit = false;
+        }
+    }
+
+    // term: factor, with *, x or / between factors
+    fn term(&mut self) -> Result<(Number, bool), String> {
+        let (mut value, mut had_unit) = self.factor()?;
+        loop {
+            match self.peek() {
+                Some('*') => self.pos += 1,
+                Some('x' | 'X') if self.word().len() == 1 => self.pos += 1,
+                Some('/') => {
+                    self.pos += 1;
+                    value = value.div(self.factor()?.0)?;
+                    had_unit = false;
+                    continue;
+                },
+                _ => return Ok((value, had_unit)),
+            }
+            value = value.mul(self.factor()?.0)?;
+            had_unit = false;
+        }
+    }
+
+    // factor: a number or an expression in brackets, optionally negated and followed by a unit
+    fn factor(&mut self) -> Result<(Number, bool), String> {
+        let value = match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                let (v, unit) = self.nested(Self::factor)?;
+                return Ok((v.neg()?, unit));
+            },
+            Some('+') => {
+                self.pos += 1;
+                return self.nested(Self::factor);
+            },
+            Some('(') => {
+                self.pos += 1;
+                let v = self.nested(Self::expression)?;
+                if self.peek() != Some(')') {
+                    return Err(format!("Missing a closing bracket at position {}", self.position()));
+                }
+                self.pos += 1;
+                v
+            },
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number()?,
+            Some(c) => return Err(format!("Expected a number at position {}, but found `{}`", self.position(), c)),
+            None => return Err("Expected a number, but the input ended".to_string()),
+        };
+
+        // Numbers can be directly followed by a unit, like 1.5k or 2 stacks
+        let word = self.word();
+        if let Some(multiplier) = self.unit(word) {
+            self.pos += word.len();
+            return Ok((value.mul(multiplier)?, true));
+        }
+        Ok((value, false))
+    }
+
+    fn number(&mut self) -> Result<Number, String> {
+        let rest = self.rest();
+        let mut digits = String::new();
+        let mut end = rest.len();
+        for (i, c) in rest.char_indices() {
+            if c.is_ascii_digit() || (c == '.' && !digits.contains('.')) {
+                digits.push(c);
+            // Allow commas between thousands, like 1,000,000
+            } else if c == ',' && !digits.is_empty() && !digits.contains('.')
+                && rest[i + 1..].chars().take_while(|c| c.is_ascii_digit()).count() == 3 {
+                continue;
+            } else {
+                end = i;
+                break;
+            }
+        }
+        let position = self.position();
+        self.pos += end;
+        let invalid = || format!("`{}` at position {} is not a number", &rest[..end], position);
+        if digits.contains('.') {
+            return Number::fraction(digits.parse::<f64>().map_err(|_| invalid())?);
+        }
+        match digits.parse::<i64>() {
+            Ok(n) => Ok(Number::Whole(n)),
+            // Only digits, so it can only fail by being too large
+            Err(_) if !digits.is_empty() => Err(TOO_LARGE.to_string()),
+            Err(_) => Err(invalid()),
+        }
+    }
+}
+
+// Read a number from the start of the input, returning it and the text after it
+fn parse_number(input: &str, stack: Option<u64>) -> Result<(Number, &str), String> {
+    let stack = match stack {
+        Some(s) => Some(i64::try_from(s).map_err(|_| TOO_LARGE.to_string())?),
+        None => None,
+    };
+    let mut parser = Parser { input: input.trim(), pos: 0, stack, depth: 0 };
+    if parser.peek().is_none() {
+        return Err("No amount given".to_string());
+    }
+    let value = parser.expression()?;
+    parser.skip_whitespace();
+    Ok((value, parser.rest()))
+}
+
+// Read an amount from the start of the input as a float, for amounts that can be fractions
+pub fn parse_amount(input: &str, stack: Option<u64>) -> Result<(f64, &str), String> {
+    let (value, rest) = parse_number(input, stack)?;
+    Ok((value.exact()?, rest))
+}
+
+// Read an amount that has to be a positive whole number, like an amount of items
+pub fn parse_count(input: &str, stack: Option<u64>) -> Result<(u64, &str), String> {
+    match parse_number(input, stack)? {
+        (Number::Whole(n), rest) if n >= 0 => Ok((n as u64, rest)),
+        (Number::Whole(_), _) => Err("The amount can't be negative".to_string()),
+        (Number::Fraction(f), _) if f < 0.0 => Err("The amount can't be negative".to_string()),
+        (Number::Fraction(f), _) => Err(format!("{} is not a whole number", f)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_numbers_are_exact() {
+        assert_eq!(parse_count("9007199254740993", None), Ok((9007199254740993, "")));
+        assert_eq!(parse_count("4611686018427387903 * 2 + 1", None), Ok((i64::MAX as u64, "")));
+        assert_eq!(parse_count("9223372036854775807 + 1", None), Err(TOO_LARGE.to_string()));
+        assert_eq!(parse_count("99999999999999999999", None), Err(TOO_LARGE.to_string()));
+        assert_eq!(parse_count("3*64+5 dirt", None), Ok((197, "dirt")));
+        assert_eq!(parse_count("2 stacks 10", Some(16)), Ok((42, "")));
+        assert_eq!(parse_count("1 sb", Some(64)), Ok((1728, "")));
+    }
+
+    #[test]
+    fn fractions_fall_back_to_floats() {
+        assert_eq!(parse_count("1.5k", None), Ok((1500, "")));
+        assert_eq!(parse_count("10 / 4 * 2", None), Ok((5, "")));
+        assert_eq!(parse_count("10 / 4", None), Err("2.5 is not a whole number".to_string()));
+        assert_eq!(parse_amount("-2.5", None), Ok((-2.5, "")));
+        // Mixing a fraction with a whole number too large for a float would round it
+        assert_eq!(parse_amount("9007199254740993 * 0.5", None), Err(TOO_LARGE.to_string()));
+        assert_eq!(parse_amount("1 / 0", None), Err("Can't divide by zero".to_string()));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth: usize| "(".repeat(depth) + "1" + &")".repeat(depth);
+        assert_eq!(parse_count(&nested(MAX_DEPTH), None), Ok((1, "")));
+        assert_eq!(parse_count(&nested(MAX_DEPTH + 1), None), Err("That expression is nested too deeply".to_string()));
+        assert_eq!(parse_count(&("(".repeat(4000) + "1"), None), Err("That expression is nested too deeply".to_string()));
+        assert_eq!(parse_count(&("-".repeat(4000) + "1"), None), Err("That expression is nested too deeply".to_string()));
+        assert_eq!(parse_amount(&("-(+".repeat(22) + "1"), None), Err("That expression is nested too deeply".to_string()));
+    }
+}
@@ -0,0 +1,287 @@
+use serde_json::Value;
+
+// The colours of the legacy formatting codes 0 to f, with their names in text components
+const COLOURS: [(char, &str, u32); 16] = [
+    ('0', "black", 0x000000),
+    ('1', "dark_blue", 0x0000AA),
+    ('2', "dark_green", 0x00AA00),
+    ('3', "dark_aqua", 0x00AAAA),
+    ('4', "dark_red", 0xAA0000),
+    ('5', "dark_purple", 0xAA00AA),
+    ('6', "gold", 0xFFAA00),
+    ('7', "gray", 0xAAAAAA),
+    ('8', "dark_gray", 0x555555),
+    ('9', "blue", 0x5555FF),
+    ('a', "green", 0x55FF55),
+    ('b', "aqua", 0x55FFFF),
+    ('c', "red", 0xFF5555),
+    ('d', "light_purple", 0xFF55FF),
+    ('e', "yellow", 0xFFFF55),
+    ('f', "white", 0xFFFFFF),
+];
+
+// The colours Discord shows in ansi code blocks with their codes, as plain colours since Discord's own are muted
+const ANSI_COLOURS: [(u8, u32); 8] = [
+    (30, 0x808080),
+    (31, 0xFF0000),
+    (32, 0x00FF00),
+    (33, 0xFFFF00),
+    (34, 0x0000FF),
+    (35, 0xFF00FF),
+    (36, 0x00FFFF),
+    (37, 0xFFFFFF),
+];
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub colour: Option<u32>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub strikethrough: bool,
+    pub obfuscated: bool,
+}
+
+// A piece of text that all has the same style
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+fn push(spans: &mut Vec<Span>, text: &str, style: Style) {
+    if text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ => spans.push(Span { text: text.to_string(), style }),
+    }
+}
+
+// Read a colour like #FF5555 or a name like red
+pub fn parse_colour(colour: &str) -> Option<u32> {
+    match colour.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 => u32::from_str_radix(hex, 16).ok(),
+        Some(_) => None,
+        None => COLOURS.iter().find(|(_c, name, _rgb)| *name == colour).map(|(_c, _name, rgb)| *rgb),
+    }
+}
+
+// Split text with § codes into spans, starting from a style. & codes are only read when ampersand is set, as they are
+// shorthand used in plugin configs, while text from servers can have a literal & like in "rock&roll"
+fn legacy_spans(text: &str, ampersand: bool, mut style: Style, spans: &mut Vec<Span>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let code = chars.get(i + 1).map(|c| c.to_ascii_lowercase());
+        if (c != '§' && !(ampersand && c == '&')) || code.is_none() {
+            current.push(c);
+            i += 1;
+            continue;
+        }
+        let code = code.unwrap_or_default();
+        // Hex colours, either §x§R§R§G§G§B§B or &#RRGGBB
+        let hex = match code {
+            'x' if i + 14 <= chars.len() => Some((chars[i + 2..i + 14].iter().skip(1).step_by(2).collect::<String>(), 14)),
+            '#' if i + 8 <= chars.len() => Some((chars[i + 2..i + 8].iter().collect::<String>(), 8)),
+            _ => None,
+        };
+        let hex = hex.and_then(|(h, length)| u32::from_str_radix(&h, 16).ok().map(|rgb| (rgb, length)));
+        let next = if let Some((rgb, length)) = hex {
+            Some((Style { colour: Some(rgb), ..Default::default() }, length))
+        } else if let Some((_c, _name, rgb)) = COLOURS.iter().find(|(c, _name, _rgb)| *c == code) {
+            // Colours also reset the formatting
+            Some((Style { colour: Some(*rgb), ..Default::default() }, 2))
+        } else {
+            match code {
+                'k' => Some((Style { obfuscated: true, ..style }, 2)),
+                'l' => Some((Style { bold: true, ..style }, 2)),
+                'm' => Some((Style { strikethrough: true, ..style }, 2)),
+                'n' => Some((Style { underlined: true, ..style }, 2)),
+                'o' => Some((Style { italic: true, ..style }, 2)),
+                'r' => Some((Style::default(), 2)),
+                _ => None,
+            }
+        };
+        match next {
+            Some((next, length)) => {
+                push(spans, &current, style);
+                current.clear();
+                style = next;
+                i += length;
+            },
+            // Not a code, like "Tom & Jerry"
+            None => {
+                current.push(c);
+                i += 1;
+            },
+        }
+    }
+    push(spans, &current, style);
+}
+
+// Split a JSON text component into spans, where parts inherit the style of their parent
+fn component_spans(component: &Value, parent: Style, spans: &mut Vec<Span>) {
+    match component {
+        Value::String(s) => legacy_spans(s, false, parent, spans),
+        Value::Number(_) | Value::Bool(_) => push(spans, &component.to_string(), parent),
+        Value::Array(parts) => {
+            for part in parts {
+                component_spans(part, parent, spans);
+            }
+        },
+        Value::Object(o) => {
+            let mut style = parent;
+            if let Some(colour) = o.get("color").and_then(|c| c.as_str()).and_then(parse_colour) {
+                style.colour = Some(colour);
+            }
+            for (key, flag) in [
+                ("bold", &mut style.bold),
+                ("italic", &mut style.italic),
+                ("underlined", &mut style.underlined),
+                ("strikethrough", &mut style.strikethrough),
+                ("obfuscated", &mut style.obfuscated),
+            ] {
+                if let Some(b) = o.get(key).and_then(|b| b.as_bool()) {
+                    *flag = b;
+                }
+            }
+            // Translated and keybind text can't be looked up here, so show the key
+            let text = ["text", "translate", "keybind"].iter().find_map(|k| o.get(*k).and_then(|t| t.as_str()));
+            legacy_spans(text.unwrap_or_default(), false, style, spans);
+            if let Some(Value::Array(extra)) = o.get("extra") {
+                for part in extra {
+                    component_spans(part, style, spans);
+                }
+            }
+        },
+        Value::Null => {},
+    }
+}
+
+// Split text into styled spans, reading it as a JSON text component if it is one and with § or & codes otherwise
+pub fn parse(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let trimmed = text.trim();
+    match serde_json::from_str::<Value>(trimmed) {
+        Ok(component) if trimmed.starts_with(['{', '[', '"']) => component_spans(&component, Style::default(), &mut spans),
+        _ => legacy_spans(text, true, Style::default(), &mut spans),
+    }
+    spans
+}
+
+// Split a JSON text component that was already parsed, like a server's MOTD, into styled spans
+pub fn parse_component(component: &Value) -> Vec<Span> {
+    let mut spans = Vec::new();
+    component_spans(component, Style::default(), &mut spans);
+    spans
+}
+
+// The text without any styling
+pub fn plain(spans: &[Span]) -> String {
+    spans.iter().map(|s| s.text.as_str()).collect()
+}
+
+// Remove § formatting codes from text sent by a server, like RCON output
+pub fn strip_codes(text: &str) -> String {
+    let mut spans = Vec::new();
+    legacy_spans(text, false, Style::default(), &mut spans);
+    plain(&spans)
+}
+
+// The ansi colour code Discord can show that is closest to a colour
+fn nearest_ansi(rgb: u32) -> u8 {
+    let channels = |c: u32| [(c >> 16 & 0xFF) as i64, (c >> 8 & 0xFF) as i64, (c & 0xFF) as i64];
+    let target = channels(rgb);
+    ANSI_COLOURS.iter()
+        .min_by_key(|(_code, colour)| channels(*colour).iter().zip(target).map(|(a, b)| (a - b) * (a - b)).sum::<i64>())
+        .map_or(37, |(code, _colour)| *code)
+}
+
+// Format the spans for a Discord ansi code block, which only supports bold, underlines and 8 colours
+pub fn ansi(spans: &[Span]) -> String {
+    let mut out = String::new();
+    for span in spans {
+        let mut codes = vec!["0".to_string()];
+        if span.style.bold {
+            codes.push("1".to_string());
+        }
+        if span.style.underlined {
+            codes.push("4".to_string());
+        }
+        if let Some(colour) = span.style.colour {
+            codes.push(nearest_ansi(colour).to_string());
+        }
+        out.push_str(&format!("\u{1b}[{}m{}", codes.join(";"), span.text));
+    }
+    // Keep the text from closing the code block early
+    out.replace("```", "`\u{200b}``")
+}
+
+// Keys a JSON text component can have, from all versions
+const COMPONENT_KEYS: [&str; 32] = [
+    "text", "translate", "with", "fallback", "keybind", "score", "selector", "separator", "nbt", "interpret",
+    "block", "entity", "storage", "source", "type", "object", "sprite", "atlas", "player", "hat",
+    "color", "shadow_color", "font", "bold", "italic", "underlined", "strikethrough", "obfuscated",
+    "insertion", "clickEvent", "hoverEvent", "extra",
+];
+
+// Find mistakes in a JSON text component that Minecraft would reject or ignore, like misspelled keys
+pub fn component_warnings(component: &Value, path: &str, warnings: &mut Vec<String>) {
+    match component {
+        Value::Array(parts) => {
+            if parts.is_empty() {
+                warnings.push(format!("`{}` is an empty list, which isn't a valid component", path));
+            }
+            for (i, part) in parts.iter().enumerate() {
+                component_warnings(part, &format!("{}[{}]", path, i), warnings);
+            }
+        },
+        Value::Object(o) => {
+            for (key, value) in o {
+                let at = format!("{}.{}", path, key);
+                match key.as_str() {
+                    "color" => match value.as_str() {
+                        Some(c) if parse_colour(c).is_some() => {},
+                        _ => warnings.push(format!("`{}` should be a colour name like \"red\" or a hex colour like \"#FF5555\"", at)),
+                    },
+                    "bold" | "italic" | "underlined" | "strikethrough" | "obfuscated" | "interpret" if !value.is_boolean() =>
+                        warnings.push(format!("`{}` should be true or false, without quotes", at)),
+                    "extra" | "with" => match value {
+                        Value::Array(parts) if !parts.is_empty() => component_warnings(value, &at, warnings),
+                        _ => warnings.push(format!("`{}` should be a list of components that isn't empty", at)),
+                    },
+                    "separator" => component_warnings(value, &at, warnings),
+                    // The newer names for these, from 1.21.5
+                    "click_event" | "hover_event" => {},
+                    k if !COMPONENT_KEYS.contains(&k) => warnings.push(format!("`{}` is not a key text components use", at)),
+                    _ => {},
+                }
+            }
+            if !["text", "translate", "keybind", "score", "selector", "nbt", "sprite", "player"].iter().any(|k| o.contains_key(*k)) {
+                warnings.push(format!("`{}` has no content, like `text` or `translate`", path));
+            }
+        },
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_only_section_codes_from_server_text() {
+        assert_eq!(strip_codes("§cRed §lbold§r rock&roll"), "Red bold rock&roll");
+        assert_eq!(strip_codes("§x§f§f§5§5§5§5Hex &#FF5555kept"), "Hex &#FF5555kept");
+    }
+
+    #[test]
+    fn reads_ampersand_codes_in_config_text() {
+        assert_eq!(plain(&parse("&c[Admin] &lSteve")), "[Admin] Steve");
+        assert_eq!(plain(&parse("Tom & Jerry")), "Tom & Jerry");
+        assert_eq!(plain(&parse(r#"{"text":"rock&roll"}"#)), "rock&roll");
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use toml::from_str;
+
+// A Minecraft item and how many of it fit in one stack
+#[derive(Deserialize, Clone, Debug)]
+pub struct Item {
+    pub name: String,
+    pub stack: u32,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ItemTable {
+    items: Vec<Item>,
+}
+
+// Get the bundled item table, parsed the first time it's needed
+pub fn items() -> &'static [Item] {
+    static ITEMS: OnceLock<Vec<Item>> = OnceLock::new();
+    ITEMS.get_or_init(|| {
+        let table: ItemTable = from_str(include_str!("../../assets/items.toml")).expect("Failed to parse items.toml");
+        table.items
+    })
+}
+
+// Lowercase a name and remove anything that isn't a letter or number, so "Ender_Pearl" and "ender pearl" are the same
+pub fn normalise(name: &str) -> String {
+    name.trim().trim_start_matches("minecraft:").to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+// The amount of single character edits needed to turn one string into another
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb { previous } else { 1 + previous.min(row[j]).min(current) };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+// Find the closest of a list of names, allowing a small typo for every few characters
+pub fn closest<'a, T>(input: &str, options: impl Iterator<Item = (&'a str, T)>) -> Option<T> {
+    let input = normalise(input);
+    if input.is_empty() {
+        return None;
+    }
+    let mut best: Option<(usize, T)> = None;
+    for (name, value) in options {
+        let name = normalise(name);
+        // Also accept plurals like pearls or torches
+        let distance = [input.as_str(), input.trim_end_matches('s'), input.trim_end_matches("es")].iter()
+            .map(|i| levenshtein(i, &name))
+            .min()
+            .unwrap_or(usize::MAX);
+        if distance == 0 {
+            return Some(value);
+        }
+        if distance <= input.len() / 4 && best.as_ref().is_none_or(|(d, _v)| distance < *d) {
+            best = Some((distance, value));
+        }
+    }
+    best.map(|(_d, v)| v)
+}
+
+// Find an item by its name or one of its aliases
+pub fn find_item(name: &str) -> Option<&'static Item> {
+    let options = items().iter().flat_map(|item|
+        std::iter::once(item.name.as_str()).chain(item.aliases.iter().map(|a| a.as_str())).map(move |n| (n, item)));
+    closest(name, options)
+}
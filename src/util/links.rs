@@ -0,0 +1,30 @@
+use rand::Rng;
+
+use crate::config::Config;
+use crate::storage::Storage;
+
+// Letters and numbers for link codes, without ones that are easy to mix up like O and 0
+const CODE_CHARACTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+
+// The Discord user a Minecraft player is linked to, set in the config or linked with !link
+pub fn linked_user(config: &Config, storage: &Storage, player: &str) -> Option<u64> {
+    config.linked_accounts.iter()
+        .chain(storage.links.iter())
+        .find(|(name, _id)| name.eq_ignore_ascii_case(player))
+        .map(|(_name, id)| *id)
+}
+
+// The Minecraft player linked to a Discord user
+pub fn linked_player(config: &Config, storage: &Storage, user: u64) -> Option<String> {
+    config.linked_accounts.iter()
+        .chain(storage.links.iter())
+        .find(|(_name, id)| **id == user)
+        .map(|(name, _id)| name.to_string())
+}
+
+// A random code for a player to confirm linking with
+pub fn new_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LENGTH).map(|_| CODE_CHARACTERS[rng.gen_range(0..CODE_CHARACTERS.len())] as char).collect()
+}
@@ -0,0 +1,162 @@
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use regex::Regex;
+use serde::Deserialize;
+use toml::from_str;
+
+// The rules the bot comes with, written to the rule file if it doesn't exist yet
+const DEFAULT_RULES: &str = include_str!("../../assets/log_rules.toml");
+
+// A known problem to look for in logs, with the explanation to reply with
+#[derive(Deserialize)]
+struct LogRule {
+    name: String,
+    pattern: String,
+    response: String,
+}
+
+#[derive(Deserialize)]
+struct RuleFile {
+    rules: Vec<LogRule>,
+}
+
+// A rule with its pattern compiled, so it only has to be compiled once
+pub struct Rule {
+    pub name: String,
+    pub regex: Regex,
+    pub response: String,
+}
+
+// The rules from the rule file, loaded the first time they are needed and again when the config is reloaded
+static RULES: RwLock<Option<Arc<Vec<Rule>>>> = RwLock::new(None);
+
+// Read and compile the rules in a rule file, leaving out ones with invalid patterns
+fn parse_rules(file: &str, path: &str) -> Vec<Rule> {
+    let rules = match from_str::<RuleFile>(file) {
+        Ok(r) => r.rules,
+        Err(e) => {
+            println!("Failed to parse {}: {}", path, e);
+            return Vec::new();
+        },
+    };
+    rules.into_iter().filter_map(|rule| match Regex::new(&rule.pattern) {
+        Ok(regex) => Some(Rule { name: rule.name, regex, response: rule.response }),
+        Err(e) => {
+            println!("Invalid pattern for log rule {}: {}", rule.name, e);
+            None
+        },
+    }).collect()
+}
+
+// Load the rules from a file, creating it with the default rules if it doesn't exist
+fn load_rules(path: &str) -> Vec<Rule> {
+    let file = match read_to_string(PathBuf::from(path)) {
+        Ok(f) => f,
+        Err(_) => {
+            if let Err(e) = write(PathBuf::from(path), DEFAULT_RULES) {
+                println!("Failed to write {}: {}", path, e);
+            }
+            DEFAULT_RULES.to_string()
+        },
+    };
+    parse_rules(&file, path)
+}
+
+// The rules to look through logs with, loading them from the file if they weren't yet
+pub fn rules(path: &str) -> Arc<Vec<Rule>> {
+    if let Some(rules) = RULES.read().unwrap().as_ref() {
+        return rules.clone();
+    }
+    reload_rules(path)
+}
+
+// Load the rules from the file again, like after the config is reloaded
+pub fn reload_rules(path: &str) -> Arc<Vec<Rule>> {
+    let rules = Arc::new(load_rules(path));
+    *RULES.write().unwrap() = Some(rules.clone());
+    rules
+}
+
+// Find the known problems in a log, as the names of the rules that matched and their explanations
+pub fn analyze(log: &str, rules: &[Rule], max_matches: usize) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = Vec::new();
+    for rule in rules {
+        for caps in rule.regex.captures_iter(log) {
+            let mut response = String::new();
+            caps.expand(&rule.response, &mut response);
+            // The same problem is often logged more than once
+            if !found.iter().any(|(name, r)| *name == rule.name && *r == response) {
+                found.push((rule.name.to_string(), response));
+            }
+            if found.len() >= max_matches {
+                return found;
+            }
+        }
+    }
+    found
+}
+
+// Get the contents of code blocks in a message, as logs are often pasted in them
+pub fn code_blocks(content: &str) -> Vec<&str> {
+    content.split("```").skip(1).step_by(2).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A sample log for every default rule, in the order of the rule file, and what its explanation should mention
+    const FIXTURES: &[(&str, &str)] = &[
+        (include_str!("../../tests/fixtures/logs/fabric-missing-dependency.log"), "**Sodium Extra** needs **sodium**"),
+        (include_str!("../../tests/fixtures/logs/forge-missing-dependency.log"), "**alexsmobs** needs **geckolib** (version [4.2,))"),
+        (include_str!("../../tests/fixtures/logs/crash-newer-java.txt"), "class file version 65.0, while Java only supports up to 61.0"),
+        (include_str!("../../tests/fixtures/logs/unsupported-class-file.log"), "(class file version 65)"),
+        (include_str!("../../tests/fixtures/logs/crash-out-of-memory.txt"), "ran out of memory"),
+        (include_str!("../../tests/fixtures/logs/mixin-failure.log"), "**create** failed to change the game's code (`create.mixins.json:accessor.AbstractProjectileDispenseBehaviorAccessor`)"),
+        (include_str!("../../tests/fixtures/logs/port-in-use.log"), "port is already being used"),
+    ];
+
+    fn default_rules() -> Vec<Rule> {
+        parse_rules(DEFAULT_RULES, "log_rules.toml")
+    }
+
+    #[test]
+    fn every_default_rule_matches_its_fixture() {
+        let rules = default_rules();
+        assert_eq!(rules.len(), FIXTURES.len(), "every default rule needs a fixture");
+        for (i, (rule, (log, expected))) in rules.iter().zip(FIXTURES).enumerate() {
+            let found = analyze(log, std::slice::from_ref(rule), 10);
+            assert_eq!(found.len(), 1, "rule {} ({}) found {:?}", i, rule.name, found);
+            assert!(found[0].1.contains(expected), "rule {} ({}) said {:?}", i, rule.name, found[0].1);
+            // No other rule should match the same log
+            assert_eq!(analyze(log, &rules, 10), found, "fixture for rule {} ({})", i, rule.name);
+        }
+    }
+
+    #[test]
+    fn clean_log_matches_nothing() {
+        let log = include_str!("../../tests/fixtures/logs/clean.log");
+        assert!(analyze(log, &default_rules(), 10).is_empty());
+    }
+
+    #[test]
+    fn stops_at_max_matches() {
+        let log = FIXTURES.iter().map(|(log, _)| *log).collect::<Vec<_>>().join("\n");
+        assert_eq!(analyze(&log, &default_rules(), 10).len(), FIXTURES.len());
+        assert_eq!(analyze(&log, &default_rules(), 3).len(), 3);
+    }
+
+    #[test]
+    fn skips_invalid_patterns() {
+        let file = "[[rules]]\nname = \"Bad\"\npattern = \"(\"\nresponse = \"\"\n\n[[rules]]\nname = \"Good\"\npattern = \"x\"\nresponse = \"\"\n";
+        let rules = parse_rules(file, "test.toml");
+        assert_eq!(rules.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["Good"]);
+    }
+
+    #[test]
+    fn finds_code_blocks() {
+        assert_eq!(code_blocks("look ```one``` and ```two``` ```unclosed"), ["one", "two", "unclosed"]);
+    }
+}
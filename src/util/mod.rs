@@ -1,3 +1,21 @@
+pub mod anvil;
+pub mod applications;
+pub mod bridge;
+pub mod colours;
+pub mod containers;
+pub mod duration;
+pub mod expression;
+pub mod format;
+pub mod items;
+pub mod links;
+pub mod logs;
 pub mod perms;
+pub mod ping;
+pub mod png;
+pub mod rcon;
+pub mod recipes;
 pub mod regexresponse;
-pub mod response;
\ No newline at end of file
+pub mod response;
+pub mod slime;
+pub mod snbt;
+pub mod uuid;
\ No newline at end of file
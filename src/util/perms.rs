@@ -1,5 +1,9 @@
+use chrono::Utc;
+use serenity::model::Permissions;
+
 use crate::config::Role;
 use crate::config::Config;
+use crate::storage::Storage;
 
 // Check permission with function
 pub fn check_permission(config: &Config, perm: String, role: &Role) -> bool {
@@ -25,3 +29,227 @@ pub fn check_permission(config: &Config, perm: String, role: &Role) -> bool {
         }
     }
 }
+
+// Where a message was sent, used for permissions that only apply in certain channels
+#[derive(Default)]
+pub struct Scope {
+    pub channel: u64,
+    pub category: u64,
+    pub name: String,
+}
+
+impl Scope {
+    // Check if a qualifier like 123456781234567812, <#123456781234567812> or #events points at the channel itself
+    fn is_channel(&self, qualifier: &str) -> bool {
+        if let Some(name) = qualifier.strip_prefix('#') {
+            return !self.name.is_empty() && name.eq_ignore_ascii_case(&self.name);
+        }
+        qualifier_id(qualifier).is_some_and(|id| id != 0 && id == self.channel)
+    }
+
+    // Check if a qualifier points at the category the channel is in
+    fn is_category(&self, qualifier: &str) -> bool {
+        qualifier_id(qualifier).is_some_and(|id| id != 0 && id == self.category)
+    }
+
+    fn matches(&self, qualifier: &str) -> bool {
+        self.is_channel(qualifier) || self.is_category(qualifier)
+    }
+}
+
+// A permission without its @channel qualifier, like cmd.timezone for cmd.timezone@#events
+pub fn unscoped(perm: &str) -> &str {
+    perm.split_once('@').map_or(perm, |(perm, _qualifier)| perm)
+}
+
+fn qualifier_id(qualifier: &str) -> Option<u64> {
+    qualifier.trim_start_matches("<#").trim_end_matches('>').parse::<u64>().ok()
+}
+
+// Keep the permissions that apply in this scope, removing the @channel qualifier from them
+fn scoped_perms<'a>(perms: &'a [String], scope: &'a Scope) -> impl Iterator<Item = String> + 'a {
+    perms.iter().filter_map(move |p| match p.split_once('@') {
+        Some((perm, qualifier)) if scope.matches(qualifier) => Some(perm.to_string()),
+        Some(_) => None,
+        None => Some(p.to_string()),
+    })
+}
+
+// Create a role for a user in a channel, holding their own overrides and grants followed by the
+// permissions of the role they got and all roles it inherits, so check_permission only has to look at one list
+pub fn user_role(config: &Config, storage: &Storage, role_name: &str, user: u64, scope: &Scope) -> Role {
+    let now = Utc::now().timestamp();
+    // Temporary grants come first so they take priority, newest first
+    let grants: Vec<String> = storage.grants.iter().rev()
+        .filter(|g| g.user == user && g.is_active(now))
+        .map(|g| g.perm.to_string())
+        .collect();
+    let mut perms: Vec<String> = scoped_perms(&grants, scope).collect();
+    // Then the overrides set in the config
+    if let Some(overrides) = config.users.get(&user.to_string()) {
+        perms.extend(scoped_perms(overrides, scope));
+    }
+
+    // Then walk through the role and the roles it inherits
+    let mut seen: Vec<&str> = Vec::new();
+    let mut next = role_name;
+    while !next.is_empty() && !seen.contains(&next) {
+        seen.push(next);
+        let role = match config.roles.get(next) {
+            Some(r) => r,
+            None => break,
+        };
+        // Permissions set for this channel come before the ones for its category
+        for (qualifier, channel_perms) in &role.channels {
+            if scope.is_channel(qualifier) {
+                perms.extend(scoped_perms(channel_perms, scope));
+            }
+        }
+        for (qualifier, channel_perms) in &role.channels {
+            if scope.is_category(qualifier) {
+                perms.extend(scoped_perms(channel_perms, scope));
+            }
+        }
+        perms.extend(scoped_perms(&role.perms, scope));
+        next = &role.inherit;
+    }
+
+    Role {
+        inherit: "".to_string(),
+        perms,
+        ..Default::default()
+    }
+}
+
+// Check if a set of Discord permissions contains one by name, like Administrator or MANAGE_MESSAGES
+pub fn has_discord_permission(permissions: Permissions, name: &str) -> bool {
+    let normalise = |n: &str| n.chars().filter(|c| c.is_ascii_alphabetic()).collect::<String>().to_lowercase();
+    let name = normalise(name);
+    permissions.get_permission_names().iter().any(|p| normalise(p) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Grant;
+
+    const USER: u64 = 42;
+    const EVENTS: u64 = 1000;
+    const CATEGORY: u64 = 2000;
+
+    fn perms(list: &[&str]) -> Vec<String> {
+        list.iter().map(|p| p.to_string()).collect()
+    }
+
+    // A member role that inherits the default role, which has channel and category specific permissions
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.roles.clear();
+        let mut default = Role { perms: perms(&["cmd.mcxp", "cmd.timezone", "-cmd.uptime", "cmd.mcslime@#events"]), ..Default::default() };
+        default.channels.insert(EVENTS.to_string(), perms(&["-cmd.mcxp"]));
+        default.channels.insert(CATEGORY.to_string(), perms(&["cmd.mcxp", "cmd.uptime"]));
+        config.roles.insert("default".to_string(), default);
+        config.roles.insert("member".to_string(), Role { inherit: "default".to_string(), perms: perms(&["cmd.link"]), ..Default::default() });
+        config
+    }
+
+    fn events() -> Scope {
+        Scope { channel: EVENTS, category: CATEGORY, name: "events".to_string() }
+    }
+
+    fn general() -> Scope {
+        Scope { channel: 3000, category: CATEGORY, name: "general".to_string() }
+    }
+
+    fn check(config: &Config, storage: &Storage, perm: &str, scope: &Scope) -> bool {
+        check_permission(config, perm.to_string(), &user_role(config, storage, "member", USER, scope))
+    }
+
+    #[test]
+    fn roles_inherit_permissions() {
+        let (config, storage) = (config(), Storage::default());
+        assert!(check(&config, &storage, "cmd.link", &Scope::default()));
+        assert!(check(&config, &storage, "cmd.timezone", &Scope::default()));
+        assert!(!check(&config, &storage, "cmd.uptime", &Scope::default()));
+        assert!(!check(&config, &storage, "cmd.mcuuid", &Scope::default()));
+    }
+
+    #[test]
+    fn channel_beats_category_beats_role() {
+        let (config, storage) = (config(), Storage::default());
+        assert!(!check(&config, &storage, "cmd.mcxp", &events()));
+        assert!(check(&config, &storage, "cmd.mcxp", &general()));
+        assert!(check(&config, &storage, "cmd.uptime", &general()));
+        assert!(!check(&config, &storage, "cmd.uptime", &Scope { channel: 3000, ..Default::default() }));
+    }
+
+    #[test]
+    fn qualifiers_match_and_are_stripped() {
+        let (config, storage) = (config(), Storage::default());
+        let role = user_role(&config, &storage, "member", USER, &events());
+        assert!(role.perms.iter().all(|p| !p.contains('@')));
+        assert!(check(&config, &storage, "cmd.mcslime", &events()));
+        assert!(!check(&config, &storage, "cmd.mcslime", &general()));
+
+        let scope = events();
+        for qualifier in ["#events", "#EVENTS", "1000", "<#1000>", "2000", "<#2000>"] {
+            assert!(scope.matches(qualifier), "{}", qualifier);
+        }
+        for qualifier in ["#general", "3000", "0", "events"] {
+            assert!(!scope.matches(qualifier), "{}", qualifier);
+        }
+        // Categories aren't named, and nothing matches outside of a channel
+        assert!(!Scope::default().matches("#events") && !Scope::default().matches("0"));
+
+        assert_eq!(unscoped("cmd.timezone@#events"), "cmd.timezone");
+        assert_eq!(unscoped("-bypass.regex@<#1000>"), "-bypass.regex");
+        assert_eq!(unscoped("cmd.timezone"), "cmd.timezone");
+        // Like when a user with a scoped permission grants it to someone else
+        assert!(check_permission(&config, unscoped("cmd.mcslime@#events").to_string(), &role));
+    }
+
+    #[test]
+    fn grants_beat_overrides_beat_roles() {
+        let mut config = config();
+        config.users.insert(USER.to_string(), perms(&["cmd.uptime", "-cmd.link", "cmd.mcuuid@#events"]));
+        let mut storage = Storage::default();
+        assert!(check(&config, &storage, "cmd.uptime", &general()));
+        assert!(!check(&config, &storage, "cmd.link", &general()));
+        assert!(check(&config, &storage, "cmd.mcuuid", &events()));
+        assert!(!check(&config, &storage, "cmd.mcuuid", &general()));
+
+        let now = Utc::now().timestamp();
+        storage.grants.push(Grant { user: USER, perm: "cmd.link".to_string(), expires: None });
+        storage.grants.push(Grant { user: USER, perm: "-cmd.uptime@#general".to_string(), expires: Some(now + 3600) });
+        storage.grants.push(Grant { user: USER, perm: "cmd.mcuuid".to_string(), expires: Some(now - 1) });
+        storage.grants.push(Grant { user: USER + 1, perm: "-cmd.timezone".to_string(), expires: None });
+        assert!(check(&config, &storage, "cmd.link", &general()));
+        assert!(!check(&config, &storage, "cmd.uptime", &general()));
+        assert!(check(&config, &storage, "cmd.uptime", &events()));
+        // Expired grants and other users' grants are ignored
+        assert!(!check(&config, &storage, "cmd.mcuuid", &general()));
+        assert!(check(&config, &storage, "cmd.timezone", &general()));
+
+        // The newest grant for a permission wins
+        storage.grants.push(Grant { user: USER, perm: "-cmd.link".to_string(), expires: None });
+        assert!(!check(&config, &storage, "cmd.link", &general()));
+    }
+
+    #[test]
+    fn inheritance_loops_stop() {
+        let mut config = config();
+        config.roles.get_mut("default").unwrap().inherit = "member".to_string();
+        let role = user_role(&config, &Storage::default(), "member", USER, &Scope::default());
+        assert!(check_permission(&config, "cmd.link".to_string(), &role));
+        assert!(!check_permission(&config, "cmd.mcuuid".to_string(), &role));
+        assert!(user_role(&config, &Storage::default(), "missing", USER, &Scope::default()).perms.is_empty());
+    }
+
+    #[test]
+    fn discord_permission_names() {
+        let permissions = Permissions::MANAGE_MESSAGES | Permissions::KICK_MEMBERS;
+        assert!(has_discord_permission(permissions, "Manage Messages"));
+        assert!(has_discord_permission(permissions, "MANAGE_MESSAGES"));
+        assert!(!has_discord_permission(permissions, "Administrator"));
+    }
+}
@@ -0,0 +1,334 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout_at;
+
+use crate::util::format::{parse_component, plain, strip_codes};
+
+// What a server reports about itself in the server list
+#[derive(Clone, Debug, Default)]
+pub struct ServerStatus {
+    pub version: String,
+    pub online: u64,
+    pub max: u64,
+    pub sample: Vec<String>,
+    // The MOTD without its formatting codes
+    pub motd: String,
+    pub latency: u128,
+}
+
+#[derive(Debug)]
+pub enum PingError {
+    Io(std::io::Error),
+    Timeout,
+    Protocol(String),
+}
+
+impl std::error::Error for PingError {}
+
+impl fmt::Display for PingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PingError::Io(e) => write!(f, "{}", e),
+            PingError::Timeout => write!(f, "timed out"),
+            PingError::Protocol(s) => write!(f, "invalid response, {}", s),
+        }
+    }
+}
+
+impl From<std::io::Error> for PingError {
+    fn from(e: std::io::Error) -> Self {
+        PingError::Io(e)
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+async fn read_varint<R: AsyncRead + Unpin>(reader: &mut R) -> Result<i32, PingError> {
+    let mut value: u32 = 0;
+    for i in 0..5 {
+        let byte = reader.read_u8().await?;
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err(PingError::Protocol("VarInt is too big".to_string()))
+}
+
+// Prefix a packet with its length
+fn packet(id: i32, data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    write_varint(&mut body, id);
+    body.extend_from_slice(data);
+    let mut out = Vec::new();
+    write_varint(&mut out, body.len() as i32);
+    out.extend_from_slice(&body);
+    out
+}
+
+// Split an address into a host and port, using the default Minecraft port if there is none
+pub fn split_address(address: &str) -> (String, u16) {
+    match address.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(p) => (host.to_string(), p),
+            Err(_) => (address.to_string(), 25565),
+        },
+        None => (address.to_string(), 25565),
+    }
+}
+
+// Parse the JSON a server sends in response to a status request
+pub fn parse_status(json: &str) -> Result<ServerStatus, PingError> {
+    let v: Value = serde_json::from_str(json).map_err(|e| PingError::Protocol(e.to_string()))?;
+    let players = &v["players"];
+    Ok(ServerStatus {
+        version: v["version"]["name"].as_str().unwrap_or_default().to_string(),
+        online: players["online"].as_u64().unwrap_or_default(),
+        max: players["max"].as_u64().unwrap_or_default(),
+        sample: players["sample"].as_array().map(|s| s.iter()
+            .filter_map(|p| p["name"].as_str().map(|n| n.to_string()))
+            .collect()).unwrap_or_default(),
+        motd: plain(&parse_component(&v["description"])),
+        latency: 0,
+    })
+}
+
+// Ping a 1.7+ server with the current protocol, over a connection made at the time given
+async fn ping_modern(mut stream: TcpStream, host: &str, port: u16, started: Instant) -> Result<ServerStatus, PingError> {
+
+    // Handshake with protocol version -1 as we don't know it yet, and next state 1 for status
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, -1);
+    write_varint(&mut handshake, host.len() as i32);
+    handshake.extend_from_slice(host.as_bytes());
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1);
+    stream.write_all(&packet(0x00, &handshake)).await?;
+    stream.write_all(&packet(0x00, &[])).await?;
+
+    let _length = read_varint(&mut stream).await?;
+    if read_varint(&mut stream).await? != 0x00 {
+        return Err(PingError::Protocol("expected a status response".to_string()));
+    }
+    let json_length = read_varint(&mut stream).await?;
+    if !(0..=1 << 20).contains(&json_length) {
+        return Err(PingError::Protocol(format!("status is {} bytes long", json_length)));
+    }
+    let mut json = vec![0; json_length as usize];
+    stream.read_exact(&mut json).await?;
+    let mut status = parse_status(&String::from_utf8_lossy(&json))?;
+    status.latency = started.elapsed().as_millis();
+    Ok(status)
+}
+
+// Ping a server from before 1.7, which answers with a kicking packet holding its status
+async fn ping_legacy(host: &str, port: u16) -> Result<ServerStatus, PingError> {
+    let started = Instant::now();
+    let mut stream = TcpStream::connect((host, port)).await?;
+    stream.write_all(&[0xFE, 0x01]).await?;
+    if stream.read_u8().await? != 0xFF {
+        return Err(PingError::Protocol("expected a kick packet".to_string()));
+    }
+    let length = stream.read_u16().await? as usize;
+    let mut chars = Vec::with_capacity(length);
+    for _ in 0..length {
+        chars.push(stream.read_u16().await?);
+    }
+    let text = String::from_utf16_lossy(&chars);
+
+    // 1.4 and up: §1, protocol, version, MOTD, online, max, separated by null characters
+    let parts: Vec<&str> = if text.starts_with("§1\0") {
+        text.split('\0').skip(2).collect()
+    // Before 1.4: MOTD, online, max, separated by §
+    } else {
+        let mut p: Vec<&str> = text.rsplitn(3, '§').collect();
+        p.reverse();
+        p.insert(0, "");
+        p
+    };
+    if parts.len() < 4 {
+        return Err(PingError::Protocol("legacy status is missing fields".to_string()));
+    }
+    Ok(ServerStatus {
+        version: parts[0].to_string(),
+        motd: strip_codes(parts[1]),
+        online: parts[2].parse().unwrap_or_default(),
+        max: parts[3].parse().unwrap_or_default(),
+        latency: started.elapsed().as_millis(),
+        ..Default::default()
+    })
+}
+
+// Get the status of a server, trying the legacy ping if the server doesn't understand the current one,
+// all within the timeout
+pub async fn ping(address: &str, seconds: u64) -> Result<ServerStatus, PingError> {
+    let (host, port) = split_address(address);
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(seconds);
+    let started = Instant::now();
+    // A server that can't be reached won't answer the legacy ping either
+    let stream = match timeout_at(deadline, TcpStream::connect((host.as_str(), port))).await {
+        Ok(result) => result?,
+        Err(_) => return Err(PingError::Timeout),
+    };
+    match timeout_at(deadline, ping_modern(stream, &host, port, started)).await {
+        Ok(Ok(status)) => Ok(status),
+        // Servers from before 1.7 hang up on the current ping or answer with something else
+        Ok(Err(_)) => match timeout_at(deadline, ping_legacy(&host, port)).await {
+            Ok(result) => result,
+            Err(_) => Err(PingError::Timeout),
+        },
+        Err(_) => Err(PingError::Timeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn varints_round_trip() {
+        for value in [0, 1, 127, 128, 255, 25565, 2097151, i32::MAX, -1, i32::MIN] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            assert_eq!(read_varint(&mut buf.as_slice()).await.unwrap(), value);
+        }
+        let mut buf = Vec::new();
+        write_varint(&mut buf, 300);
+        assert_eq!(buf, [0xAC, 0x02]);
+        assert!(matches!(read_varint(&mut [0x80u8; 6].as_slice()).await, Err(PingError::Protocol(_))));
+    }
+
+    #[test]
+    fn parses_status_json() {
+        let status = parse_status(r#"{"version":{"name":"1.20.4","protocol":765},"players":{"max":20,"online":2,
+            "sample":[{"name":"Steve","id":"x"},{"name":"Alex","id":"y"}]},"description":{"text":"A ","extra":[{"text":"server","color":"gold"}]}}"#).unwrap();
+        assert_eq!(status.version, "1.20.4");
+        assert_eq!((status.online, status.max), (2, 20));
+        assert_eq!(status.sample, ["Steve", "Alex"]);
+        assert_eq!(status.motd, "A server");
+
+        let status = parse_status(r#"{"version":{"name":"Paper 1.8.8"},"players":{"max":10,"online":0},"description":"§aGreen MOTD"}"#).unwrap();
+        assert_eq!(status.motd, "Green MOTD");
+        assert!(status.sample.is_empty());
+        assert!(parse_status("not json").is_err());
+    }
+
+    #[test]
+    fn splits_addresses() {
+        assert_eq!(split_address("play.example.com"), ("play.example.com".to_string(), 25565));
+        assert_eq!(split_address("127.0.0.1:25566"), ("127.0.0.1".to_string(), 25566));
+    }
+
+    // Read a packet a client sent to a modern server, returning its ID and data
+    async fn read_packet(stream: &mut TcpStream) -> (i32, Vec<u8>) {
+        let length = read_varint(stream).await.unwrap() as usize;
+        let mut body = vec![0; length];
+        stream.read_exact(&mut body).await.unwrap();
+        let mut reader = body.as_slice();
+        let id = read_varint(&mut reader).await.unwrap();
+        (id, reader.to_vec())
+    }
+
+    #[tokio::test]
+    async fn pings_modern_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            // The handshake has protocol -1, the address and next state 1 for status
+            let (id, handshake) = read_packet(&mut stream).await;
+            let mut expected = Vec::new();
+            write_varint(&mut expected, -1);
+            write_varint(&mut expected, 9);
+            expected.extend_from_slice(b"127.0.0.1");
+            expected.extend_from_slice(&port.to_be_bytes());
+            write_varint(&mut expected, 1);
+            assert_eq!((id, handshake), (0x00, expected));
+            assert_eq!(read_packet(&mut stream).await, (0x00, Vec::new()));
+
+            let json = r#"{"version":{"name":"1.21"},"players":{"max":50,"online":7},"description":"Hello"}"#;
+            let mut data = Vec::new();
+            write_varint(&mut data, json.len() as i32);
+            data.extend_from_slice(json.as_bytes());
+            stream.write_all(&packet(0x00, &data)).await.unwrap();
+        });
+
+        let status = ping(&format!("127.0.0.1:{}", port), 2).await.unwrap();
+        assert_eq!(status.version, "1.21");
+        assert_eq!((status.online, status.max), (7, 50));
+        assert_eq!(status.motd, "Hello");
+    }
+
+    // A server from before 1.7, which hangs up on the modern ping and answers 0xFE with a kick packet
+    async fn legacy_server(text: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 2];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, [0xFE, 0x01]);
+            let chars: Vec<u16> = text.encode_utf16().collect();
+            let mut kick = vec![0xFF];
+            kick.extend_from_slice(&(chars.len() as u16).to_be_bytes());
+            for c in chars {
+                kick.extend_from_slice(&c.to_be_bytes());
+            }
+            stream.write_all(&kick).await.unwrap();
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_legacy_ping() {
+        let port = legacy_server("§1\x0078\x001.4.7\x00A §cold§r server\x003\x0020").await;
+        let status = ping(&format!("127.0.0.1:{}", port), 2).await.unwrap();
+        assert_eq!(status.version, "1.4.7");
+        assert_eq!(status.motd, "A old server");
+        assert_eq!((status.online, status.max), (3, 20));
+
+        // Before 1.4 there is only the MOTD and player counts
+        let port = legacy_server("Beta server§1§10").await;
+        let status = ping(&format!("127.0.0.1:{}", port), 2).await.unwrap();
+        assert_eq!(status.motd, "Beta server");
+        assert_eq!((status.online, status.max), (1, 10));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_timeout() {
+        // A server that accepts the connection but never answers isn't pinged again with the legacy ping
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+        let started = Instant::now();
+        assert!(matches!(ping(&format!("127.0.0.1:{}", port), 1).await, Err(PingError::Timeout)));
+        assert!(started.elapsed() < Duration::from_millis(1500));
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_when_refused() {
+        // Bind and drop a listener to get a port nothing is listening on
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        assert!(matches!(ping(&format!("127.0.0.1:{}", port), 2).await, Err(PingError::Io(_))));
+    }
+}
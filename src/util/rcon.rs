@@ -0,0 +1,242 @@
+use std::fmt;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use crate::config::RconConfig;
+
+// Packet types of the Source RCON protocol
+const RESPONSE_VALUE: i32 = 0;
+const EXEC_COMMAND: i32 = 2;
+const AUTH: i32 = 3;
+
+// Minecraft splits responses into packets of this many characters, which can take up to 3 bytes each in UTF-8
+const MAX_BODY: usize = 4096;
+const MAX_BODY_BYTES: usize = MAX_BODY * 3;
+
+// Minecraft reads packets of up to 1460 bytes, leaving this many for the command
+const MAX_COMMAND: usize = 1446;
+
+#[derive(Debug)]
+pub enum RconError {
+    Io(std::io::Error),
+    Auth,
+    Timeout,
+    TooLong,
+    Protocol(String),
+}
+
+impl std::error::Error for RconError {}
+
+impl fmt::Display for RconError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RconError::Io(e) => write!(f, "Could not reach the server: {}", e),
+            RconError::Auth => write!(f, "The server refused the RCON password"),
+            RconError::Timeout => write!(f, "The server took too long to respond"),
+            RconError::TooLong => write!(f, "The command is too long to send over RCON"),
+            RconError::Protocol(s) => write!(f, "The server sent an invalid response: {}", s),
+        }
+    }
+}
+
+impl From<std::io::Error> for RconError {
+    fn from(e: std::io::Error) -> Self {
+        RconError::Io(e)
+    }
+}
+
+pub struct Rcon {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl Rcon {
+    // Connect to a server and log in with the password
+    pub async fn connect(address: &str, password: &str) -> Result<Self, RconError> {
+        let stream = TcpStream::connect(address).await?;
+        let mut rcon = Rcon { stream, next_id: 1 };
+        let id = rcon.send(AUTH, password).await?;
+        // Servers may send an empty response value before the auth response
+        loop {
+            let (response_id, kind, _body) = rcon.read().await?;
+            if response_id == -1 {
+                return Err(RconError::Auth);
+            }
+            if kind == EXEC_COMMAND && response_id == id {
+                return Ok(rcon);
+            }
+        }
+    }
+
+    // Run a command and return what the server responded with
+    pub async fn command(&mut self, command: &str) -> Result<String, RconError> {
+        if command.len() > MAX_COMMAND {
+            return Err(RconError::TooLong);
+        }
+        let id = self.send(EXEC_COMMAND, command).await?;
+        let mut output = String::new();
+        let mut marker = None;
+        loop {
+            let (response_id, kind, body) = self.read().await?;
+            if kind != RESPONSE_VALUE {
+                continue;
+            }
+            if marker == Some(response_id) {
+                return Ok(output);
+            }
+            if response_id != id {
+                continue;
+            }
+            output.push_str(&body);
+            // Long responses are split over several packets without saying which one is the last, so an empty packet is sent
+            // as a marker, which the server answers after the rest of the response. It's only sent once the first part
+            // arrived, as Minecraft drops packets that arrive together with the one it is reading.
+            if marker.is_none() {
+                marker = Some(self.send(RESPONSE_VALUE, "").await?);
+            }
+        }
+    }
+
+    async fn send(&mut self, kind: i32, body: &str) -> Result<i32, RconError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        // The length covers the ID, type, body and two null bytes
+        let mut packet = Vec::with_capacity(body.len() + 14);
+        packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        self.stream.write_all(&packet).await?;
+        Ok(id)
+    }
+
+    async fn read(&mut self) -> Result<(i32, i32, String), RconError> {
+        let length = self.stream.read_i32_le().await?;
+        if !(10..=MAX_BODY_BYTES as i32 + 10).contains(&length) {
+            return Err(RconError::Protocol(format!("packet length {}", length)));
+        }
+        let id = self.stream.read_i32_le().await?;
+        let kind = self.stream.read_i32_le().await?;
+        let mut body = vec![0; length as usize - 8];
+        self.stream.read_exact(&mut body).await?;
+        // Strip the two null bytes at the end
+        body.truncate(body.len() - 2);
+        Ok((id, kind, String::from_utf8_lossy(&body).to_string()))
+    }
+}
+
+// Connect to the configured server, run a single command and disconnect, giving up after the timeout
+pub async fn run_command(config: &RconConfig, command: &str) -> Result<String, RconError> {
+    let run = async {
+        let mut rcon = Rcon::connect(&config.address, &config.password).await?;
+        rcon.command(command).await
+    };
+    match timeout(Duration::from_secs(config.timeout), run).await {
+        Ok(result) => result,
+        Err(_) => Err(RconError::Timeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    async fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &str) {
+        let mut packet = Vec::new();
+        packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        stream.write_all(&packet).await.unwrap();
+    }
+
+    // A fake server that answers like Minecraft, one packet at a time, splitting the output of every command into packets of MAX_BODY characters
+    async fn fake_server(password: &'static str, output: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Ok(length) = stream.read_i32_le().await {
+                let id = stream.read_i32_le().await.unwrap();
+                let kind = stream.read_i32_le().await.unwrap();
+                let mut body = vec![0; length as usize - 8];
+                stream.read_exact(&mut body).await.unwrap();
+                let body = String::from_utf8_lossy(&body[..body.len() - 2]).to_string();
+                match kind {
+                    AUTH if body == password => write_packet(&mut stream, id, EXEC_COMMAND, "").await,
+                    AUTH => write_packet(&mut stream, -1, EXEC_COMMAND, "").await,
+                    EXEC_COMMAND => {
+                        let chars: Vec<char> = output.chars().collect();
+                        let parts: Vec<String> = if chars.is_empty() { vec![String::new()] } else { chars.chunks(MAX_BODY).map(|c| c.iter().collect()).collect() };
+                        for part in parts {
+                            write_packet(&mut stream, id, RESPONSE_VALUE, &part).await;
+                        }
+                    },
+                    _ => write_packet(&mut stream, id, RESPONSE_VALUE, &format!("Unknown request {:x}", kind)).await,
+                }
+            }
+        });
+        address
+    }
+
+    fn config(address: String, password: &str) -> RconConfig {
+        RconConfig { address, password: password.to_string(), timeout: 2 }
+    }
+
+    #[tokio::test]
+    async fn runs_command_with_good_password() {
+        let address = fake_server("hunter2", "There are 0 of a max of 20 players online: ".to_string()).await;
+        let output = run_command(&config(address, "hunter2"), "list").await.unwrap();
+        assert_eq!(output, "There are 0 of a max of 20 players online: ");
+    }
+
+    #[tokio::test]
+    async fn refuses_bad_password() {
+        let address = fake_server("hunter2", String::new()).await;
+        assert!(matches!(run_command(&config(address, "wrong"), "list").await, Err(RconError::Auth)));
+    }
+
+    #[tokio::test]
+    async fn times_out_when_server_does_not_answer() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+        let config = RconConfig { address, password: "hunter2".to_string(), timeout: 1 };
+        assert!(matches!(run_command(&config, "list").await, Err(RconError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn joins_responses_split_over_packets() {
+        for length in [MAX_BODY - 1, MAX_BODY, MAX_BODY * 2, MAX_BODY * 2 + 100] {
+            let expected: String = (0..length).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+            let address = fake_server("hunter2", expected.to_string()).await;
+            let output = run_command(&config(address, "hunter2"), "whitelist list").await.unwrap();
+            assert_eq!(output, expected, "response of {} characters", length);
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_packets_of_multibyte_characters() {
+        // Section signs take 2 bytes in UTF-8 and € and 漢 take 3, so these packets are well over 4096 bytes
+        let expected: String = "§a€漢".repeat(MAX_BODY / 4 * 3);
+        let address = fake_server("hunter2", expected.to_string()).await;
+        let output = run_command(&config(address, "hunter2"), "list").await.unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[tokio::test]
+    async fn refuses_commands_too_long_to_send() {
+        let address = fake_server("hunter2", String::new()).await;
+        let command = "say ".to_string() + &"a".repeat(MAX_COMMAND);
+        assert!(matches!(run_command(&config(address, "hunter2"), &command).await, Err(RconError::TooLong)));
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{read_dir, read_to_string};
+use std::sync::{Arc, RwLock};
+
+use serde_json::{Map, Value};
+
+use crate::util::items::{find_item, normalise};
+
+// Recipes are followed this many levels deep at most
+const MAX_DEPTH: usize = 16;
+
+// A crafting recipe, with the items or tags it needs (tags start with #) and how many items it makes
+pub struct Recipe {
+    pub ingredients: Vec<(String, u64)>,
+    pub count: u64,
+}
+
+// Read an ingredient, which is an id or #tag in new data packs, {"item": id} or {"tag": tag} in old ones,
+// or a list of those when any of them can be used, in which case the first is used
+fn ingredient(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.to_string()),
+        Value::Array(options) => options.first().and_then(ingredient),
+        Value::Object(o) => match (o.get("item"), o.get("tag")) {
+            (Some(Value::String(item)), _) => Some(item.to_string()),
+            (_, Some(Value::String(tag))) => Some(format!("#{}", tag)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Count how many of each ingredient are needed, keeping them in the order they first appear
+fn count_ingredients(list: impl Iterator<Item = String>) -> Vec<(String, u64)> {
+    let mut counts: Vec<(String, u64)> = Vec::new();
+    for id in list {
+        match counts.iter_mut().find(|(i, _count)| *i == id) {
+            Some((_id, count)) => *count += 1,
+            None => counts.push((id, 1)),
+        }
+    }
+    counts
+}
+
+// Read a crafting recipe in the data pack format, returning the id of what it makes
+pub fn parse_recipe(recipe: &Map<String, Value>) -> Option<(String, Recipe)> {
+    let ingredients = match recipe.get("type")?.as_str()?.trim_start_matches("minecraft:") {
+        "crafting_shaped" => {
+            let key = recipe.get("key")?.as_object()?;
+            let symbols = recipe.get("pattern")?.as_array()?.iter()
+                .filter_map(|row| row.as_str())
+                .flat_map(|row| row.chars())
+                .filter(|c| *c != ' ')
+                .map(|c| key.get(&c.to_string()).and_then(ingredient))
+                .collect::<Option<Vec<String>>>()?;
+            count_ingredients(symbols.into_iter())
+        },
+        "crafting_shapeless" => {
+            let list = recipe.get("ingredients")?.as_array()?.iter().map(ingredient).collect::<Option<Vec<String>>>()?;
+            count_ingredients(list.into_iter())
+        },
+        // Smelting and other recipes make raw materials, like iron ingots, which are where the breakdown stops
+        _ => return None,
+    };
+    let (id, count) = match recipe.get("result")? {
+        Value::String(id) => (id.to_string(), 1),
+        Value::Object(result) => {
+            let id = result.get("id").or_else(|| result.get("item"))?.as_str()?;
+            (id.to_string(), result.get("count").and_then(|c| c.as_u64()).unwrap_or(1))
+        },
+        _ => return None,
+    };
+    if ingredients.is_empty() || count == 0 {
+        return None;
+    }
+    Some((id, Recipe { ingredients, count }))
+}
+
+// Add recipes by their file names, where a recipe named after the item it makes (like piston for a piston)
+// is preferred over others for the same item (like stick_from_bamboo_item for sticks), and otherwise the first one is used
+fn add_recipes(recipes: &mut HashMap<String, Recipe>, files: BTreeMap<String, Map<String, Value>>) {
+    let mut added: HashMap<String, bool> = HashMap::new();
+    for (name, recipe) in files {
+        let Some((id, recipe)) = parse_recipe(&recipe) else {
+            continue;
+        };
+        let named = id.trim_start_matches("minecraft:") == name;
+        // A recipe replaces an earlier one for the same item only when it is named after the item and the earlier one isn't
+        let replace = match added.get(&id) {
+            None => true,
+            Some(earlier_named) => named && !earlier_named,
+        };
+        if replace {
+            added.insert(id.to_string(), named);
+            recipes.insert(id, recipe);
+        }
+    }
+}
+
+// The recipes, loaded the first time they are needed and again when the config is reloaded
+static RECIPES: RwLock<Option<Arc<HashMap<String, Recipe>>>> = RwLock::new(None);
+
+// Load the bundled recipes, then the recipe files in a directory, which replace the bundled ones for the same item
+fn load_recipes(dir: &str) -> HashMap<String, Recipe> {
+    let mut recipes = HashMap::new();
+    let bundled: BTreeMap<String, Map<String, Value>> = serde_json::from_str(include_str!("../../assets/recipes.json"))
+        .expect("Failed to parse recipes.json");
+    add_recipes(&mut recipes, bundled);
+
+    let mut files = BTreeMap::new();
+    if let Ok(entries) = read_dir(dir) {
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            let name = path.file_stem().map_or(String::new(), |s| s.to_string_lossy().to_string());
+            match read_to_string(&path).map_err(|e| e.to_string()).and_then(|f| serde_json::from_str(&f).map_err(|e| e.to_string())) {
+                Ok(recipe) => {
+                    files.insert(name, recipe);
+                },
+                Err(e) => println!("Failed to read recipe {}: {}", path.display(), e),
+            }
+        }
+    }
+    add_recipes(&mut recipes, files);
+    recipes
+}
+
+// The recipes to craft with, loading them if they weren't yet
+pub fn recipes(dir: &str) -> Arc<HashMap<String, Recipe>> {
+    if let Some(recipes) = RECIPES.read().unwrap().as_ref() {
+        return recipes.clone();
+    }
+    reload_recipes(dir)
+}
+
+// Load the recipes again, like after the config is reloaded, to pick up changes to the recipe files
+pub fn reload_recipes(dir: &str) -> Arc<HashMap<String, Recipe>> {
+    let recipes = Arc::new(load_recipes(dir));
+    *RECIPES.write().unwrap() = Some(recipes.clone());
+    recipes
+}
+
+// The name of an item or tag, from the item table if it's in there, like Redstone Dust for minecraft:redstone
+pub fn display_name(id: &str) -> String {
+    let tag = id.starts_with('#');
+    let path = id.trim_start_matches('#').rsplit(':').next().unwrap_or_default().replace('_', " ");
+    if tag {
+        return format!("Any {}", title_case(&path));
+    }
+    // Only exact names, as a close match could be a different item
+    let name = normalise(&path);
+    match find_item(&path) {
+        Some(item) if normalise(&item.name) == name || item.aliases.iter().any(|a| normalise(a) == name) => item.name.to_string(),
+        _ => title_case(&path),
+    }
+}
+
+fn title_case(text: &str) -> String {
+    text.split(' ')
+        .enumerate()
+        .map(|(i, word)| match word {
+            "of" | "and" | "on" | "a" | "the" if i > 0 => word.to_string(),
+            _ => {
+                let mut chars = word.chars();
+                chars.next().map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+            },
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// What crafting something takes, with the raw materials, the items crafted on the way and what is left over
+#[derive(Default)]
+pub struct Breakdown {
+    pub raw: Vec<(String, u64)>,
+    pub crafted: Vec<(String, u64)>,
+    pub left_over: Vec<(String, u64)>,
+}
+
+fn add(list: &mut Vec<(String, u64)>, id: &str, amount: u64) {
+    match list.iter_mut().find(|(i, _amount)| i == id) {
+        Some((_id, a)) => *a = a.saturating_add(amount),
+        None => list.push((id.to_string(), amount)),
+    }
+}
+
+// Whether following the recipes from an item leads to one of the targets, like redstone blocks back to redstone
+fn leads_to(recipes: &HashMap<String, Recipe>, id: &str, targets: &[&str], seen: &mut HashSet<String>) -> bool {
+    if !seen.insert(id.to_string()) {
+        return false;
+    }
+    recipes.get(id).is_some_and(|recipe| recipe.ingredients.iter()
+        .any(|(i, _count)| targets.contains(&i.as_str()) || leads_to(recipes, i, targets, seen)))
+}
+
+// Whether crafting an ingredient would go in a circle, either back to something that is being crafted or back to itself,
+// like redstone from redstone blocks, which are made from redstone
+fn circular(recipes: &HashMap<String, Recipe>, id: &str, stack: &[String]) -> bool {
+    let mut targets: Vec<&str> = stack.iter().map(|s| s.as_str()).collect();
+    targets.push(id);
+    leads_to(recipes, id, &targets, &mut HashSet::new())
+}
+
+fn expand(recipes: &HashMap<String, Recipe>, id: &str, amount: u64, stack: &mut Vec<String>, spare: &mut HashMap<String, u64>, out: &mut Breakdown) {
+    // Use what was left over from crafting something else first
+    let from_spare = spare.get(id).copied().unwrap_or(0).min(amount);
+    if from_spare > 0 {
+        spare.insert(id.to_string(), spare[id] - from_spare);
+    }
+    let amount = amount - from_spare;
+    if amount == 0 {
+        return;
+    }
+
+    let recipe = match recipes.get(id) {
+        // The item asked for is always crafted, but ingredients are raw materials when crafting them would go in a circle
+        Some(recipe) if stack.is_empty() || (stack.len() < MAX_DEPTH && !circular(recipes, id, stack)) => recipe,
+        _ => {
+            add(&mut out.raw, id, amount);
+            return;
+        },
+    };
+
+    let crafts = amount.div_ceil(recipe.count);
+    let made = crafts.saturating_mul(recipe.count);
+    *spare.entry(id.to_string()).or_insert(0) += made - amount;
+    if !stack.is_empty() {
+        add(&mut out.crafted, id, made);
+    }
+    stack.push(id.to_string());
+    for (ingredient, count) in &recipe.ingredients {
+        expand(recipes, ingredient, count.saturating_mul(crafts), stack, spare, out);
+    }
+    stack.pop();
+}
+
+// Work out the raw materials needed to craft an amount of an item, following the recipes all the way down
+pub fn materials(recipes: &HashMap<String, Recipe>, id: &str, amount: u64) -> Breakdown {
+    let mut out = Breakdown::default();
+    let mut spare = HashMap::new();
+    expand(recipes, id, amount, &mut Vec::new(), &mut spare, &mut out);
+    let mut left_over: Vec<(String, u64)> = spare.into_iter().filter(|(_id, amount)| *amount > 0).collect();
+    left_over.sort();
+    out.left_over = left_over;
+    out
+}
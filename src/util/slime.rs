@@ -0,0 +1,78 @@
+// The random number generator Java uses, which Minecraft uses to pick slime chunks
+struct JavaRandom {
+    seed: i64,
+}
+
+impl JavaRandom {
+    fn new(seed: i64) -> Self {
+        Self { seed: (seed ^ 0x5DEECE66D) & ((1 << 48) - 1) }
+    }
+
+    fn next(&mut self, bits: u32) -> i32 {
+        self.seed = (self.seed.wrapping_mul(0x5DEECE66D).wrapping_add(0xB)) & ((1 << 48) - 1);
+        (self.seed >> (48 - bits)) as i32
+    }
+
+    fn next_int(&mut self, bound: i32) -> i32 {
+        loop {
+            let bits = self.next(31);
+            let value = bits % bound;
+            // Try again for the few numbers that would make some values more likely
+            if bits.wrapping_sub(value).wrapping_add(bound - 1) >= 0 {
+                return value;
+            }
+        }
+    }
+}
+
+// Turn a seed from the config into the number Minecraft uses, hashing it like Java's String.hashCode if it isn't a number
+pub fn world_seed(seed: &str) -> Option<i64> {
+    let seed = seed.trim();
+    if seed.is_empty() {
+        return None;
+    }
+    Some(match seed.parse::<i64>() {
+        Ok(s) => s,
+        Err(_) => seed.encode_utf16().fold(0i32, |hash, c| hash.wrapping_mul(31).wrapping_add(c as i32)) as i64,
+    })
+}
+
+// Whether slimes can spawn in a chunk below y 40 in a world, using the same maths as Minecraft, including Java's integer overflows
+pub fn is_slime_chunk(seed: i64, x: i32, z: i32) -> bool {
+    let seed = seed
+        .wrapping_add(x.wrapping_mul(x).wrapping_mul(0x4c1906) as i64)
+        .wrapping_add(x.wrapping_mul(0x5ac0db) as i64)
+        .wrapping_add((z.wrapping_mul(z) as i64).wrapping_mul(0x4307a7))
+        .wrapping_add(z.wrapping_mul(0x5f24f) as i64)
+        ^ 0x3ad8025f;
+    JavaRandom::new(seed).next_int(10) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The slime chunks between -5 and 5, as found by Minecraft's own formula with java.util.Random
+    fn slime_chunks(seed: i64) -> Vec<(i32, i32)> {
+        (-5..=5).flat_map(|x| (-5..=5).map(move |z| (x, z))).filter(|(x, z)| is_slime_chunk(seed, *x, *z)).collect()
+    }
+
+    #[test]
+    fn known_slime_chunks() {
+        assert_eq!(slime_chunks(0), [(-5, 5), (-2, 0), (1, -3), (2, -3), (2, 2), (2, 4), (4, 2), (5, -3)]);
+        assert_eq!(slime_chunks(12345), [(-5, 2), (-4, 0), (-2, -4), (-2, 1), (-2, 4), (-1, 2), (0, -2), (3, 0), (3, 5), (4, -5), (4, -3), (4, 1), (5, -3)]);
+        assert_eq!(slime_chunks(-4172144997902289642), [(-5, 2), (-5, 3), (-4, -4), (-3, -4), (-2, -5), (-2, 3), (-1, -5), (1, 0), (1, 1), (2, -5), (3, 0), (4, -4), (4, -2), (4, 3), (5, -5)]);
+        // Far away chunks, where the multiplications overflow like they do in Java
+        assert!(is_slime_chunk(0, 100, -200));
+        assert!(!is_slime_chunk(0, 1875000, -1875000));
+        assert!(!is_slime_chunk(0, 46341, 46341));
+    }
+
+    #[test]
+    fn text_seeds_are_hashed() {
+        assert_eq!(world_seed("12345"), Some(12345));
+        assert_eq!(world_seed(" -4172144997902289642 "), Some(-4172144997902289642));
+        assert_eq!(world_seed("minecraft"), Some(695073197));
+        assert_eq!(world_seed(""), None);
+    }
+}
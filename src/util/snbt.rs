@@ -0,0 +1,433 @@
+use std::fmt;
+
+use serde_json::{Map, Number, Value};
+
+// A value in Minecraft's NBT format
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Vec<(String, Tag)>),
+    ByteArray(Vec<i8>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+// Something wrong with SNBT, with the character it was found at counted from 1
+pub struct SnbtError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SnbtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Tag {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Tag::Byte(_) => "byte",
+            Tag::Short(_) => "short",
+            Tag::Int(_) => "int",
+            Tag::Long(_) => "long",
+            Tag::Float(_) => "float",
+            Tag::Double(_) => "double",
+            Tag::String(_) => "string",
+            Tag::List(_) => "list",
+            Tag::Compound(_) => "compound",
+            Tag::ByteArray(_) => "byte array",
+            Tag::IntArray(_) => "int array",
+            Tag::LongArray(_) => "long array",
+        }
+    }
+
+    // The same value as JSON, so text components written in SNBT can be shown
+    pub fn to_json(&self) -> Value {
+        match self {
+            // Booleans are stored as bytes
+            Tag::Byte(b) => Value::Number((*b).into()),
+            Tag::Short(s) => Value::Number((*s).into()),
+            Tag::Int(i) => Value::Number((*i).into()),
+            Tag::Long(l) => Value::Number((*l).into()),
+            Tag::Float(f) => Number::from_f64(*f as f64).map_or(Value::Null, Value::Number),
+            Tag::Double(d) => Number::from_f64(*d).map_or(Value::Null, Value::Number),
+            Tag::String(s) => Value::String(s.to_string()),
+            Tag::List(l) => Value::Array(l.iter().map(|t| t.to_json()).collect()),
+            Tag::Compound(c) => {
+                let mut map = Map::new();
+                for (key, tag) in c {
+                    // Text components use 1b for true
+                    let value = match (key.as_str(), tag) {
+                        ("bold" | "italic" | "underlined" | "strikethrough" | "obfuscated", Tag::Byte(b)) => Value::Bool(*b != 0),
+                        _ => tag.to_json(),
+                    };
+                    map.insert(key.to_string(), value);
+                }
+                Value::Object(map)
+            },
+            Tag::ByteArray(a) => Value::Array(a.iter().map(|b| Value::Number((*b).into())).collect()),
+            Tag::IntArray(a) => Value::Array(a.iter().map(|i| Value::Number((*i).into())).collect()),
+            Tag::LongArray(a) => Value::Array(a.iter().map(|l| Value::Number((*l).into())).collect()),
+        }
+    }
+
+    // Write the value as SNBT spread over lines, with lists of numbers kept on one line
+    pub fn pretty(&self, indent: usize) -> String {
+        let pad = "    ".repeat(indent + 1);
+        let end = "    ".repeat(indent);
+        match self {
+            Tag::Byte(b) => format!("{}b", b),
+            Tag::Short(s) => format!("{}s", s),
+            Tag::Int(i) => i.to_string(),
+            Tag::Long(l) => format!("{}L", l),
+            Tag::Float(f) => format!("{}f", f),
+            Tag::Double(d) => format!("{}d", d),
+            Tag::String(s) => quote(s),
+            Tag::List(l) if l.is_empty() => "[]".to_string(),
+            Tag::List(l) if l.iter().all(|t| !matches!(t, Tag::List(_) | Tag::Compound(_))) =>
+                format!("[{}]", l.iter().map(|t| t.pretty(indent)).collect::<Vec<String>>().join(", ")),
+            Tag::List(l) => format!("[\n{}\n{}]",
+                l.iter().map(|t| format!("{}{}", pad, t.pretty(indent + 1))).collect::<Vec<String>>().join(",\n"), end),
+            Tag::Compound(c) if c.is_empty() => "{}".to_string(),
+            Tag::Compound(c) => format!("{{\n{}\n{}}}",
+                c.iter().map(|(k, t)| format!("{}{}: {}", pad, key(k), t.pretty(indent + 1))).collect::<Vec<String>>().join(",\n"), end),
+            Tag::ByteArray(a) => format!("[B; {}]", a.iter().map(|b| format!("{}b", b)).collect::<Vec<String>>().join(", ")),
+            Tag::IntArray(a) => format!("[I; {}]", a.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(", ")),
+            Tag::LongArray(a) => format!("[L; {}]", a.iter().map(|l| format!("{}L", l)).collect::<Vec<String>>().join(", ")),
+        }
+    }
+}
+
+fn unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Keys only need quotes if they have other characters in them
+fn key(k: &str) -> String {
+    if !k.is_empty() && k.chars().all(unquoted) { k.to_string() } else { quote(k) }
+}
+
+// Minecraft's own limit on how deep lists and compounds can be nested, which also keeps parsing from running out of stack
+const MAX_DEPTH: usize = 512;
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn error<T>(&self, message: &str) -> Result<T, SnbtError> {
+        Err(SnbtError { position: self.pos + 1, message: message.to_string() })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SnbtError> {
+        if self.peek() != Some(c) {
+            return match self.peek() {
+                Some(found) => self.error(&format!("Expected `{}` but found `{}`", c, found)),
+                None => self.error(&format!("Expected `{}` but the text ended", c)),
+            };
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Tag, SnbtError> {
+        match self.peek() {
+            Some('{' | '[') if self.depth >= MAX_DEPTH => self.error(&format!("Values can't be nested more than {} levels deep", MAX_DEPTH)),
+            Some(c @ ('{' | '[')) => {
+                self.depth += 1;
+                let tag = if c == '{' { self.compound() } else { self.list() };
+                self.depth -= 1;
+                tag
+            },
+            Some('"' | '\'') => Ok(Tag::String(self.quoted()?)),
+            Some(c) if unquoted(c) => {
+                let start = self.pos;
+                let word = self.word();
+                match number(&word) {
+                    Some(Ok(tag)) => Ok(tag),
+                    Some(Err(message)) => Err(SnbtError { position: start + 1, message }),
+                    None => Ok(Tag::String(word)),
+                }
+            },
+            Some(c) => self.error(&format!("Expected a value but found `{}`", c)),
+            None => self.error("Expected a value but the text ended"),
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| unquoted(*c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn quoted(&mut self) -> Result<String, SnbtError> {
+        let quote = self.chars[self.pos];
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.chars.get(self.pos) {
+                Some('\\') => {
+                    match self.chars.get(self.pos + 1) {
+                        Some(c) if *c == '\\' || *c == quote => out.push(*c),
+                        // Pointing at the backslash the escape starts with
+                        Some(c) => return self.error(&format!("`\\{}` is not a valid escape, only `\\\\` and `\\{}` are", c, quote)),
+                        None => break,
+                    }
+                    self.pos += 2;
+                },
+                Some(c) if *c == quote => {
+                    self.pos += 1;
+                    return Ok(out);
+                },
+                Some(c) => {
+                    out.push(*c);
+                    self.pos += 1;
+                },
+                None => break,
+            }
+        }
+        Err(SnbtError { position: start + 1, message: "This string is never closed".to_string() })
+    }
+
+    fn compound(&mut self) -> Result<Tag, SnbtError> {
+        self.expect('{')?;
+        let mut entries: Vec<(String, Tag)> = Vec::new();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Tag::Compound(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            let key = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                Some(c) if unquoted(c) => self.word(),
+                Some(c) => return self.error(&format!("Expected a key but found `{}`", c)),
+                None => return self.error("Expected a key but the text ended"),
+            };
+            if entries.iter().any(|(k, _t)| *k == key) {
+                return Err(SnbtError { position: start + 1, message: format!("The key `{}` is used twice", key) });
+            }
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.push((key, value));
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Tag::Compound(entries));
+                },
+                Some(c) => return self.error(&format!("Expected `,` or `}}` but found `{}`", c)),
+                None => return self.error("Expected `,` or `}` but the text ended"),
+            }
+        }
+    }
+
+    fn list(&mut self) -> Result<Tag, SnbtError> {
+        self.expect('[')?;
+        // Typed arrays like [I; 1, 2, 3]
+        let kind = match (self.chars.get(self.pos), self.chars.get(self.pos + 1)) {
+            (Some(k @ ('B' | 'I' | 'L')), Some(';')) => {
+                self.pos += 2;
+                Some(*k)
+            },
+            _ => None,
+        };
+        let mut items = Vec::new();
+        if self.peek() != Some(']') {
+            loop {
+                self.skip_whitespace();
+                let start = self.pos;
+                let item = self.value()?;
+                if let Some((_s, first)) = items.first() {
+                    if kind.is_none() && std::mem::discriminant(first) != std::mem::discriminant(&item) {
+                        return Err(SnbtError { position: start + 1,
+                            message: format!("Lists can only hold one type, but this {} is in a list of {}s", item.type_name(), first.type_name()) });
+                    }
+                }
+                items.push((start, item));
+                match self.peek() {
+                    Some(',') => self.pos += 1,
+                    Some(']') => break,
+                    Some(c) => return self.error(&format!("Expected `,` or `]` but found `{}`", c)),
+                    None => return self.error("Expected `,` or `]` but the text ended"),
+                }
+            }
+        }
+        self.pos += 1;
+        let wrong = |start: usize, name: &str| SnbtError { position: start + 1, message: format!("Only {}s can go in this array", name) };
+        Ok(match kind {
+            Some('B') => Tag::ByteArray(items.into_iter().map(|(s, t)| match t {
+                Tag::Byte(b) => Ok(b),
+                _ => Err(wrong(s, "byte")),
+            }).collect::<Result<_, _>>()?),
+            Some('I') => Tag::IntArray(items.into_iter().map(|(s, t)| match t {
+                Tag::Int(i) => Ok(i),
+                _ => Err(wrong(s, "int")),
+            }).collect::<Result<_, _>>()?),
+            Some(_) => Tag::LongArray(items.into_iter().map(|(s, t)| match t {
+                Tag::Long(l) => Ok(l),
+                _ => Err(wrong(s, "long")),
+            }).collect::<Result<_, _>>()?),
+            None => Tag::List(items.into_iter().map(|(_s, t)| t).collect()),
+        })
+    }
+}
+
+// Read an unquoted word as a number, None if it is a string, or an error if it is a number out of range
+fn number(word: &str) -> Option<Result<Tag, String>> {
+    let lower = word.to_lowercase();
+    match lower.as_str() {
+        "true" => return Some(Ok(Tag::Byte(1))),
+        "false" => return Some(Ok(Tag::Byte(0))),
+        _ => {},
+    }
+    let (digits, suffix) = match lower.char_indices().last() {
+        Some((i, c @ ('b' | 's' | 'l' | 'f' | 'd'))) => (&lower[..i], Some(c)),
+        _ => (lower.as_str(), None),
+    };
+    let is_integer = !digits.is_empty() && digits.strip_prefix(['-', '+']).unwrap_or(digits).chars().all(|c| c.is_ascii_digit())
+        && digits.chars().any(|c| c.is_ascii_digit());
+    let is_decimal = !is_integer && digits.parse::<f64>().is_ok() && digits.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e'));
+    let range = |name: &str| Some(Err(format!("{} is too large for a {}", word, name)));
+    match (suffix, is_integer, is_decimal) {
+        (Some('b'), true, _) => digits.parse().map_or_else(|_e| range("byte"), |b| Some(Ok(Tag::Byte(b)))),
+        (Some('s'), true, _) => digits.parse().map_or_else(|_e| range("short"), |s| Some(Ok(Tag::Short(s)))),
+        (Some('l'), true, _) => digits.parse().map_or_else(|_e| range("long"), |l| Some(Ok(Tag::Long(l)))),
+        (None, true, _) => digits.parse().map_or_else(|_e| range("int, add L for a long"), |i| Some(Ok(Tag::Int(i)))),
+        (Some('f'), true, _) | (Some('f'), _, true) => digits.parse().ok().map(|f| Ok(Tag::Float(f))),
+        (Some('d'), true, _) | (Some('d') | None, _, true) => digits.parse().ok().map(|d| Ok(Tag::Double(d))),
+        _ => None,
+    }
+}
+
+// Parse SNBT, like {CustomName:'"Steve"',Count:1b}
+pub fn parse(input: &str) -> Result<Tag, SnbtError> {
+    let mut parser = Parser { chars: input.chars().collect(), pos: 0, depth: 0 };
+    let tag = parser.value()?;
+    if let Some(c) = parser.peek() {
+        return parser.error(&format!("Unexpected `{}` after the end of the value", c));
+    }
+    Ok(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parse and write back on one line, or the error
+    fn read(input: &str) -> String {
+        match parse(input) {
+            Ok(tag) => tag.pretty(0).replace("\n", "").replace("    ", ""),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    #[test]
+    fn typed_arrays() {
+        assert_eq!(read("[B; 1b, -2b, true]"), "[B; 1b, -2b, 1b]");
+        assert_eq!(read("[I;1,2,3]"), "[I; 1, 2, 3]");
+        assert_eq!(read("[L; 1L, 9000000000L]"), "[L; 1L, 9000000000L]");
+        assert_eq!(read("[I;]"), "[I; ]");
+        assert_eq!(read("[I; 1, 2L]"), "Error: Only ints can go in this array at position 8");
+        assert_eq!(read("[B; 1]"), "Error: Only bytes can go in this array at position 5");
+        assert_eq!(read("[L; 1L, 2]"), "Error: Only longs can go in this array at position 9");
+    }
+
+    #[test]
+    fn number_suffixes() {
+        assert_eq!(read("[1b, 2B]"), "[1b, 2b]");
+        assert_eq!(read("[3s, -4S]"), "[3s, -4s]");
+        assert_eq!(read("[5l, 6L]"), "[5L, 6L]");
+        assert_eq!(read("[1.5f, 2F]"), "[1.5f, 2f]");
+        assert_eq!(read("[1.5d, 2D, 2.5]"), "[1.5d, 2d, 2.5d]");
+        assert_eq!(read("42"), "42");
+        // Words that only look a bit like numbers are strings
+        assert_eq!(read("[1a, 12bs, minecraft.stone]"), "[\"1a\", \"12bs\", \"minecraft.stone\"]");
+    }
+
+    #[test]
+    fn out_of_range_numbers() {
+        assert_eq!(read("128b"), "Error: 128b is too large for a byte at position 1");
+        assert_eq!(read("{a: -129b}"), "Error: -129b is too large for a byte at position 5");
+        assert_eq!(read("32768s"), "Error: 32768s is too large for a short at position 1");
+        assert_eq!(read("2147483648"), "Error: 2147483648 is too large for a int, add L for a long at position 1");
+        assert_eq!(read("9223372036854775808L"), "Error: 9223372036854775808L is too large for a long at position 1");
+        assert_eq!(read("127b"), "127b");
+    }
+
+    #[test]
+    fn mixed_type_lists() {
+        assert_eq!(read("[1, 2b]"), "Error: Lists can only hold one type, but this byte is in a list of ints at position 5");
+        assert_eq!(read("[\"a\", {b: 1}]"), "Error: Lists can only hold one type, but this compound is in a list of strings at position 7");
+        assert_eq!(read("[[1], [a]]"), "[[1],[\"a\"]]");
+    }
+
+    #[test]
+    fn quoted_strings() {
+        assert_eq!(read(r#""say \"hi\"""#), r#""say \"hi\"""#);
+        assert_eq!(read(r#"'it''s'"#), "Error: Unexpected `'` after the end of the value at position 5");
+        assert_eq!(read(r#"'{"text":"Steve"}'"#), r#""{\"text\":\"Steve\"}""#);
+        assert_eq!(read(r#"'back\\slash \'quote\''"#), r#""back\\slash 'quote'""#);
+        assert_eq!(read(r#""bad \n""#), "Error: `\\n` is not a valid escape, only `\\\\` and `\\\"` are at position 6");
+        assert_eq!(read(r#"{a: "open}"#), "Error: This string is never closed at position 5");
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        assert_eq!(read("{a: 1, b: 2, a: 3}"), "Error: The key `a` is used twice at position 14");
+        assert_eq!(read("{a: 1, \"a\": 2}"), "Error: The key `a` is used twice at position 8");
+        assert_eq!(read("{a: 1, A: 2}"), "{a: 1,A: 2}");
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(read("{a 1}"), "Error: Expected `:` but found `1` at position 4");
+        assert_eq!(read("{a: 1"), "Error: Expected `,` or `}` but the text ended at position 6");
+        assert_eq!(read("[1 2]"), "Error: Expected `,` or `]` but found `2` at position 4");
+        assert_eq!(read("{a: 1} x"), "Error: Unexpected `x` after the end of the value at position 8");
+        // Positions count characters, not bytes
+        assert_eq!(read("{é: ü, b:}"), "Error: Expected a key but found `é` at position 2");
+        assert_eq!(read("{\"é\": \"ü\", b:}"), "Error: Expected a value but found `}` at position 14");
+    }
+
+    #[test]
+    fn nesting_limit() {
+        // On a stack the size of a tokio worker's, which deeper values used to overflow
+        let run = std::thread::Builder::new().stack_size(2 * 1024 * 1024).spawn(|| {
+            let deepest = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+            assert!(parse(&deepest).is_ok_and(|t| t.to_json().is_array()));
+            assert!(read(&deepest).starts_with("[["));
+            assert_eq!(read(&"{a:".repeat(MAX_DEPTH + 1)), "Error: Values can't be nested more than 512 levels deep at position 1537");
+            assert_eq!(read(&"[".repeat(4000)), "Error: Values can't be nested more than 512 levels deep at position 513");
+        }).unwrap();
+        run.join().unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+// The UUID an offline mode server gives a player, made from an MD5 hash of "OfflinePlayer:<name>" like Java's UUID.nameUUIDFromBytes
+pub fn offline_uuid(name: &str) -> [u8; 16] {
+    let mut bytes = md5::compute(format!("OfflinePlayer:{}", name)).0;
+    // Mark it as a version 3 UUID with the IETF variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x30;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    bytes
+}
+
+// Read a UUID with or without dashes
+pub fn parse_uuid(input: &str) -> Option<[u8; 16]> {
+    let hex: String = input.trim().chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+// Format a UUID like 069a79f4-44e9-4726-a5be-fca90e38aaf5
+pub fn dashed(uuid: &[u8; 16]) -> String {
+    let hex: String = uuid.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+// Format a UUID as the four signed integers NBT stores it as, like [I;110787060,1156138790,-1514210135,238594805]
+pub fn int_array(uuid: &[u8; 16]) -> String {
+    let ints: Vec<String> = uuid.chunks(4)
+        .map(|c| i32::from_be_bytes([c[0], c[1], c[2], c[3]]).to_string())
+        .collect();
+    format!("[I;{}]", ints.join(","))
+}
+
+// Whether a name is allowed for a Java Edition account, 3 to 16 letters, numbers and underscores
+pub fn valid_name(name: &str) -> bool {
+    (3..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_uuids() {
+        // The same as Java's UUID.nameUUIDFromBytes, which offline mode servers use
+        assert_eq!(dashed(&offline_uuid("Notch")), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+        assert_eq!(dashed(&offline_uuid("jeb_")), "a762f560-4fce-3236-812a-b80efff0b62b");
+        assert_eq!(dashed(&offline_uuid("Steve")), "5627dd98-e6be-3c21-b8a8-e92344183641");
+    }
+
+    #[test]
+    fn uuid_formats() {
+        let notch = parse_uuid("069a79f444e94726a5befca90e38aaf5").unwrap();
+        assert_eq!(dashed(&notch), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+        assert_eq!(parse_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5"), Some(notch));
+        assert_eq!(int_array(&notch), "[I;110787060,1156138790,-1514210135,238594805]");
+        assert_eq!(parse_uuid("069a79f4"), None);
+        assert_eq!(parse_uuid("069a79f444e94726a5befca90e38aafg"), None);
+    }
+}